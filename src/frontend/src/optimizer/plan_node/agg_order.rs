@@ -0,0 +1,75 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for order-sensitive aggregates (`FIRST_VALUE`, `LAST_VALUE`, and `ARRAY_AGG`/
+//! `STRING_AGG` with an `ORDER BY` clause) surviving the partial/total split performed by
+//! [`super::BatchSimpleAgg::to_distributed`] and [`super::BatchSortAgg::to_distributed`].
+
+use risingwave_common::util::sort_util::OrderType;
+
+use super::logical_agg::PlanAggCall;
+
+/// The ordering a single order-sensitive agg call is evaluated under, e.g. the `ORDER BY ts DESC`
+/// in `FIRST_VALUE(x ORDER BY ts DESC)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlanAggOrderBy {
+    /// Index, in the agg call's input schema, of the column the ordering is computed from.
+    pub order_key_index: usize,
+    pub order_type: OrderType,
+}
+
+/// Whether an ordered agg call picks the first or the last row under its ordering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggExtreme {
+    First,
+    Last,
+}
+
+/// Rewrites an order-sensitive `PlanAggCall` (`FIRST_VALUE`/`LAST_VALUE`, or `ARRAY_AGG`/
+/// `STRING_AGG` with an `ORDER BY`) into its partial and total halves.
+///
+/// The partial phase must not just re-run the original agg on each shard: a `FIRST_VALUE` result
+/// from one shard is not itself a valid `FIRST_VALUE` input for the total phase; it has to be
+/// merged against the other shards' winners by comparing order keys. So the partial call is
+/// rewritten to emit a `(value, order_key)` pair per group, and the total call becomes a "pick the
+/// extreme by order_key" reducer instead of a naive re-application of `FIRST_VALUE` over
+/// already-reduced rows. This keeps the partial state associative: merging two partial states is
+/// just comparing their order keys and keeping the winner, which is what the total phase does
+/// across an arbitrary number of shards.
+pub fn partial_to_total_ordered_agg_call(
+    call: &PlanAggCall,
+    order_by: &PlanAggOrderBy,
+    extreme: AggExtreme,
+) -> (PlanAggCall, PlanAggCall) {
+    let partial = call.clone_with_outputs_value_and_order_key(order_by.order_key_index);
+    let total = call.clone_as_extreme_by_order_key_reducer(order_by.clone(), extreme);
+    (partial, total)
+}
+
+/// Splits `calls` into their partial- and total-phase rewrites for a partial/total aggregation
+/// split. A call `order_by_extreme` recognizes as an order-sensitive extreme (`FIRST_VALUE`/
+/// `LAST_VALUE` with an explicit `ORDER BY`) routes through [`partial_to_total_ordered_agg_call`]
+/// so the partial phase emits a mergeable `(value, order_key)` pair instead of a bare result that
+/// can't be re-combined across shards; every other call is left untouched for the partial phase
+/// and rewritten by the plain `PlanAggCall::partial_to_total_agg_call` for the total phase.
+/// Returned in the same order as `calls`.
+pub fn partial_to_total_agg_calls(calls: &[PlanAggCall]) -> (Vec<PlanAggCall>, Vec<PlanAggCall>) {
+    calls
+        .iter()
+        .map(|call| match call.order_by_extreme() {
+            Some((order_by, extreme)) => partial_to_total_ordered_agg_call(call, &order_by, extreme),
+            None => (call.clone(), call.partial_to_total_agg_call()),
+        })
+        .unzip()
+}
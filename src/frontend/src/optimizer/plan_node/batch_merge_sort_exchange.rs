@@ -0,0 +1,81 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use risingwave_pb::batch_plan::plan_node::NodeBody;
+use risingwave_pb::batch_plan::MergeSortExchangeNode;
+
+use super::{PlanBase, PlanRef, PlanTreeNodeUnary, ToBatchProst, ToDistributedBatch};
+use crate::optimizer::property::{Distribution, Order};
+
+/// `BatchMergeSortExchange` gathers sorted shard outputs into a single globally sorted stream by
+/// performing a k-way merge on `order`, instead of `BatchExchange`'s arbitrary concatenation. Each
+/// upstream partition keeps one head element live in a binary min-heap keyed by the sort columns;
+/// the smallest is popped and its partition's next element pulled in to refill the heap. This lets
+/// a [`super::BatchSortAgg`] or an `ORDER BY` survive distribution without paying for a redundant
+/// global re-sort on the gather node.
+#[derive(Debug, Clone)]
+pub struct BatchMergeSortExchange {
+    pub base: PlanBase,
+    input: PlanRef,
+    order: Order,
+}
+
+impl BatchMergeSortExchange {
+    pub fn new(input: PlanRef, order: Order) -> Self {
+        assert!(!order.field_order.is_empty());
+        let ctx = input.ctx();
+        let base = PlanBase::new_batch(ctx, input.schema().clone(), Distribution::Single, order.clone());
+        BatchMergeSortExchange { base, input, order }
+    }
+
+    pub fn order(&self) -> &Order {
+        &self.order
+    }
+}
+
+impl fmt::Display for BatchMergeSortExchange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BatchMergeSortExchange")
+            .field("order", &self.order)
+            .finish()
+    }
+}
+
+impl PlanTreeNodeUnary for BatchMergeSortExchange {
+    fn input(&self) -> PlanRef {
+        self.input.clone()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(input, self.order.clone())
+    }
+}
+impl_plan_tree_node_for_unary! { BatchMergeSortExchange }
+
+impl ToDistributedBatch for BatchMergeSortExchange {
+    fn to_distributed(&self) -> risingwave_common::error::Result<PlanRef> {
+        // The merge exchange is itself the gather point; nothing further to distribute.
+        Ok(self.clone().into())
+    }
+}
+
+impl ToBatchProst for BatchMergeSortExchange {
+    fn to_batch_prost_body(&self) -> NodeBody {
+        NodeBody::MergeSortExchange(MergeSortExchangeNode {
+            column_orders: self.order.to_protobuf(),
+        })
+    }
+}
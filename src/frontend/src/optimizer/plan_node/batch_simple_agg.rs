@@ -20,13 +20,53 @@ use risingwave_pb::batch_plan::SortAggNode;
 
 use super::logical_agg::PlanAggCall;
 use super::{LogicalAgg, PlanBase, PlanRef, PlanTreeNodeUnary, ToBatchProst, ToDistributedBatch};
-use crate::optimizer::plan_node::{BatchExchange, ToLocalBatch};
+use crate::optimizer::plan_node::ToLocalBatch;
 use crate::optimizer::property::{Distribution, Order, RequiredDist};
 
+/// Config for the partial phase's adaptive skipping: when the grouping key is nearly unique, the
+/// partial hash table barely reduces row count before the exchange, so it's cheaper to forward raw
+/// rows as singleton partial states (which are already valid input to the total phase for any
+/// decomposable aggregate) than to build the table at all.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveAggConfig {
+    pub enable_adaptive_agg: bool,
+    /// Number of input rows to probe before deciding whether to skip local aggregation.
+    pub probe_rows: u32,
+    /// Abandon local aggregation once `distinct_groups / rows_seen` exceeds this ratio.
+    pub reduction_threshold: f32,
+}
+
+impl Default for AdaptiveAggConfig {
+    fn default() -> Self {
+        Self {
+            enable_adaptive_agg: false,
+            probe_rows: 100_000,
+            reduction_threshold: 0.8,
+        }
+    }
+}
+
+impl AdaptiveAggConfig {
+    /// Whether the partial phase should abandon its local hash table and start forwarding raw
+    /// rows as singleton partial states, given `rows_seen` input rows and `distinct_groups_seen`
+    /// distinct groups among them so far. `false` while the feature is off or fewer than
+    /// `probe_rows` rows have been seen (not enough signal to decide yet); otherwise `true` once
+    /// the distinct-group ratio crosses `reduction_threshold`. Nothing in this crate calls it; the
+    /// config reaches the executor through the `adaptive_agg_*` fields of the `SortAggNode` that
+    /// `to_batch_prost_body` emits.
+    pub fn should_skip_local_agg(&self, rows_seen: u32, distinct_groups_seen: u32) -> bool {
+        if !self.enable_adaptive_agg || rows_seen < self.probe_rows {
+            return false;
+        }
+        distinct_groups_seen as f32 / rows_seen as f32 > self.reduction_threshold
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BatchSimpleAgg {
     pub base: PlanBase,
     logical: LogicalAgg,
+    adaptive_agg_config: AdaptiveAggConfig,
 }
 
 impl BatchSimpleAgg {
@@ -40,8 +80,18 @@ impl BatchSimpleAgg {
             Distribution::SomeShard => Distribution::SomeShard,
             _ => panic!(),
         };
-        let base = PlanBase::new_batch(ctx, logical.schema().clone(), dist, Order::any().clone());
-        BatchSimpleAgg { base, logical }
+        let base = PlanBase::new_batch(ctx.clone(), logical.schema().clone(), dist, Order::any().clone());
+        let session_config = ctx.session_ctx().config();
+        let adaptive_agg_config = AdaptiveAggConfig {
+            enable_adaptive_agg: session_config.enable_adaptive_partial_agg(),
+            probe_rows: session_config.adaptive_partial_agg_probe_rows(),
+            reduction_threshold: session_config.adaptive_partial_agg_reduction_threshold(),
+        };
+        BatchSimpleAgg {
+            base,
+            logical,
+            adaptive_agg_config,
+        }
     }
 
     pub fn agg_calls(&self) -> &[PlanAggCall] {
@@ -75,30 +125,35 @@ impl ToDistributedBatch for BatchSimpleAgg {
         let dist_input = self.input().to_distributed()?;
         let plan = match dist_input.distribution() {
             Distribution::SomeShard => {
-                // 2-phase agg
-                let partial_agg = dist_input.clone();
-                let partial_agg = self.clone_with_input(partial_agg).into();
-
-                let exchange = BatchExchange::new(
-                    partial_agg,
-                    Order::any().clone(),
-                    Distribution::Single
-                ).into();
-
-                let total_agg_types = self
-                    .logical
-                    .agg_calls()
-                    .iter()
-                    .map(|agg_call| agg_call.partial_to_total_agg_call())
-                    .collect();
-                let total_agg_logical = LogicalAgg::new(
-                    total_agg_types,
-                    self.logical.group_keys().to_vec(),
-                    exchange
+                // 2-phase agg. Order-sensitive calls (`FIRST_VALUE`/`LAST_VALUE`) need their
+                // partial half rewritten too -- a bare partial result from one shard isn't a
+                // valid input for the total phase's reducer -- so both halves come from the same
+                // split instead of just rewriting the total phase afterwards.
+                let group_keys = self.logical.group_keys().to_vec();
+                let (partial_agg_calls, total_agg_calls) =
+                    super::agg_order::partial_to_total_agg_calls(self.logical.agg_calls());
+
+                let partial_logical =
+                    LogicalAgg::new(partial_agg_calls, group_keys.clone(), dist_input);
+                let partial_agg = BatchSimpleAgg::new(partial_logical).into();
+
+                // Let the context's distributed-agg strategy decide where the partial results
+                // go and which node finalizes them, instead of hard-coding gather-to-single here.
+                let strategy = self.base.ctx.distributed_agg_strategy();
+                let exchange = strategy.partial_to_total_exchange(partial_agg, &group_keys)?;
+
+                // The exchange preserves the partial agg's output schema, so the group key
+                // indices still point at the right columns on the exchanged side.
+                let total_agg_logical = super::distributed_agg_strategy::build_total_agg(
+                    total_agg_calls,
+                    group_keys,
+                    exchange,
                 );
-                let total_agg_batch = BatchSimpleAgg::new(total_agg_logical);
-                // &RequiredDist::PhysicalDist(Distribution::Single).enforce(total_agg_batch, &Order::any())
-                total_agg_batch.into()
+                // The exchange may have landed the total phase's input in an order that already
+                // satisfies the group keys (e.g. a merge exchange feeding a `BatchSortAgg` partial
+                // phase); let `new_batch_agg` pick `BatchSortAgg` instead of unconditionally
+                // finalizing with `BatchSimpleAgg`.
+                super::batch_sort_agg::new_batch_agg(total_agg_logical)
             }
             _ => {
                 let new_input = self
@@ -119,8 +174,15 @@ impl ToBatchProst for BatchSimpleAgg {
                 .iter()
                 .map(PlanAggCall::to_protobuf)
                 .collect(),
-            // We treat simple agg as a special sort agg without group keys.
-            group_keys: vec![],
+            group_keys: self
+                .logical
+                .group_keys()
+                .iter()
+                .map(|idx| *idx as i32)
+                .collect(),
+            enable_adaptive_agg: self.adaptive_agg_config.enable_adaptive_agg,
+            adaptive_agg_probe_rows: self.adaptive_agg_config.probe_rows,
+            adaptive_agg_reduction_threshold: self.adaptive_agg_config.reduction_threshold,
         })
     }
 }
@@ -0,0 +1,177 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use risingwave_common::error::Result;
+use risingwave_pb::batch_plan::plan_node::NodeBody;
+use risingwave_pb::batch_plan::SortAggNode;
+
+use super::logical_agg::PlanAggCall;
+use super::{LogicalAgg, PlanBase, PlanRef, PlanTreeNodeUnary, ToBatchProst, ToDistributedBatch};
+use crate::optimizer::plan_node::ToLocalBatch;
+use crate::optimizer::property::{Distribution, FieldOrder, Order, RequiredDist};
+
+/// `BatchSortAgg` implements [`LogicalAgg`] by streaming through input that is already sorted on
+/// the group keys, maintaining a single running accumulator set and emitting a finished group the
+/// moment the sort-key prefix changes. Unlike [`super::BatchHashAgg`] this keeps memory at O(1) in
+/// the number of groups rather than buffering every group's state in a hash table, so the
+/// optimizer should only choose it when the input already carries a useful order (e.g. from an
+/// index scan) instead of paying for an extra sort.
+#[derive(Debug, Clone)]
+pub struct BatchSortAgg {
+    pub base: PlanBase,
+    logical: LogicalAgg,
+    input_order: Order,
+}
+
+impl BatchSortAgg {
+    /// The ordering the group keys impose on `logical`'s input: the prefix a [`BatchSortAgg`]
+    /// requires of whatever feeds it, and the order its own output carries.
+    pub fn group_key_order(logical: &LogicalAgg) -> Order {
+        Order {
+            field_order: logical
+                .group_keys()
+                .iter()
+                .map(|&idx| FieldOrder::ascending(idx))
+                .collect(),
+        }
+    }
+
+    pub fn new(logical: LogicalAgg) -> Self {
+        let ctx = logical.base.ctx.clone();
+        let input = logical.input();
+        let input_dist = input.distribution();
+        let dist = match input_dist {
+            Distribution::Single => Distribution::Single,
+            // distribution phase will perform total agg for this.
+            Distribution::SomeShard | Distribution::HashShard(_) => input_dist.clone(),
+            _ => panic!(),
+        };
+        // The group keys must be the ordering prefix we require of our input.
+        let input_order = Self::group_key_order(&logical);
+        let base = PlanBase::new_batch(ctx, logical.schema().clone(), dist, input_order.clone());
+        BatchSortAgg {
+            base,
+            logical,
+            input_order,
+        }
+    }
+
+    pub fn agg_calls(&self) -> &[PlanAggCall] {
+        self.logical.agg_calls()
+    }
+
+    pub fn group_keys(&self) -> &[usize] {
+        self.logical.group_keys()
+    }
+}
+
+impl fmt::Display for BatchSortAgg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BatchSortAgg")
+            .field("group_keys", &self.group_keys())
+            .field("aggs", &self.agg_calls())
+            .finish()
+    }
+}
+
+impl PlanTreeNodeUnary for BatchSortAgg {
+    fn input(&self) -> PlanRef {
+        self.logical.input()
+    }
+
+    fn clone_with_input(&self, input: PlanRef) -> Self {
+        Self::new(self.logical.clone_with_input(input))
+    }
+}
+impl_plan_tree_node_for_unary! { BatchSortAgg }
+
+impl ToDistributedBatch for BatchSortAgg {
+    fn to_distributed(&self) -> Result<PlanRef> {
+        // Require the input to already be sorted on the group keys; if it isn't, the generic
+        // required-order enforcement inserted by the optimizer will add a sort beneath us, which
+        // defeats the purpose of this node but is still correct.
+        let dist_input = self
+            .input()
+            .to_distributed_with_required(&self.input_order, &RequiredDist::AnyShard)?;
+        let plan = match dist_input.distribution() {
+            Distribution::Single => self.clone_with_input(dist_input).into(),
+            _ => {
+                // Order-sensitive calls (`FIRST_VALUE`/`LAST_VALUE`) need their partial half
+                // rewritten too -- a bare partial result from one shard isn't a valid input for
+                // the total phase's reducer -- so both halves come from the same split instead of
+                // just rewriting the total phase afterwards.
+                let (partial_agg_calls, total_agg_calls) =
+                    super::agg_order::partial_to_total_agg_calls(self.logical.agg_calls());
+                let partial_logical = LogicalAgg::new(
+                    partial_agg_calls,
+                    self.logical.group_keys().to_vec(),
+                    dist_input,
+                );
+                // Each shard produces a sorted partial stream; gather them with a merge
+                // exchange instead of an unordered `BatchExchange` so the total phase can keep
+                // consuming sorted input without a redundant global re-sort.
+                let partial_agg = Self::new(partial_logical).into();
+                let merge_exchange =
+                    super::BatchMergeSortExchange::new(partial_agg, self.input_order.clone())
+                        .into();
+
+                let total_agg_logical = LogicalAgg::new(
+                    total_agg_calls,
+                    self.logical.group_keys().to_vec(),
+                    merge_exchange,
+                );
+                BatchSortAgg::new(total_agg_logical).into()
+            }
+        };
+        Ok(plan)
+    }
+}
+
+impl ToBatchProst for BatchSortAgg {
+    fn to_batch_prost_body(&self) -> NodeBody {
+        NodeBody::SortAgg(SortAggNode {
+            agg_calls: self
+                .agg_calls()
+                .iter()
+                .map(PlanAggCall::to_protobuf)
+                .collect(),
+            group_keys: self.group_keys().iter().map(|idx| *idx as i32).collect(),
+        })
+    }
+}
+
+impl ToLocalBatch for BatchSortAgg {
+    fn to_local(&self) -> Result<PlanRef> {
+        let new_input = self.input().to_local()?;
+        let new_input =
+            RequiredDist::AnyShard.enforce_if_not_satisfies(new_input, &self.input_order)?;
+        Ok(self.clone_with_input(new_input).into())
+    }
+}
+
+/// Picks [`BatchSortAgg`] over [`super::BatchSimpleAgg`] for `logical` when its input already
+/// carries the group-key order a sort-based agg needs, so the optimizer doesn't pay for an extra
+/// sort just to stream through an already-ordered input (e.g. from an index scan, or the sorted
+/// output of a prior [`BatchSortAgg`] phase). Falls back to [`super::BatchSimpleAgg`] otherwise,
+/// which does not require its input to be pre-sorted.
+pub fn new_batch_agg(logical: LogicalAgg) -> PlanRef {
+    let required_order = BatchSortAgg::group_key_order(&logical);
+    if logical.input().order().satisfies(&required_order) {
+        BatchSortAgg::new(logical).into()
+    } else {
+        super::BatchSimpleAgg::new(logical).into()
+    }
+}
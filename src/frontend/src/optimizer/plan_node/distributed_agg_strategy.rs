@@ -0,0 +1,72 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use risingwave_common::error::Result;
+
+use super::logical_agg::PlanAggCall;
+use super::{BatchExchange, LogicalAgg, PlanRef};
+use crate::optimizer::property::{Distribution, Order, RequiredDist};
+
+/// The type `OptimizerContext` threads through to `to_distributed` implementations via
+/// `OptimizerContext::distributed_agg_strategy`. Defaults to [`DefaultDistributedAggStrategy`].
+pub type DistributedAggStrategyRef = Arc<dyn DistributedAggStrategy>;
+
+/// Decides, for a partial/total aggregation split, where the partial results should be exchanged
+/// to and which node finalizes them. [`ToDistributedBatch`](super::ToDistributedBatch)
+/// implementations for aggregation nodes delegate to a strategy instead of hard-coding the
+/// gather-to-single policy, so specialized deployments can plug in their own placement (e.g.
+/// broadcast-small-side, or a cost-based topology-aware placement) without forking the agg plan
+/// nodes.
+pub trait DistributedAggStrategy: std::fmt::Debug {
+    /// Build the exchange that sits between the partial and total aggregation phases, given the
+    /// partial agg's plan and the group keys the total phase will aggregate on.
+    fn partial_to_total_exchange(&self, partial_agg: PlanRef, group_keys: &[usize])
+        -> Result<PlanRef>;
+}
+
+/// The strategy shipped today: no group keys gather to a single node, otherwise repartition by
+/// hashing the group keys so the total phase stays distributed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultDistributedAggStrategy;
+
+impl DistributedAggStrategy for DefaultDistributedAggStrategy {
+    fn partial_to_total_exchange(
+        &self,
+        partial_agg: PlanRef,
+        group_keys: &[usize],
+    ) -> Result<PlanRef> {
+        let dist = if group_keys.is_empty() {
+            Distribution::Single
+        } else {
+            RequiredDist::hash_shard(group_keys).to_dist()
+        };
+        Ok(BatchExchange::new(partial_agg, Order::any().clone(), dist).into())
+    }
+}
+
+/// Convenience used by `BatchSimpleAgg` to build the total-phase `LogicalAgg` from
+/// already-rewritten total-phase agg calls (see `agg_order::partial_to_total_agg_calls`, which
+/// derives `total_agg_calls` so order-sensitive calls like `FIRST_VALUE`/`LAST_VALUE` get their
+/// extreme-reducer rewrite instead of the plain one), once the strategy has decided where the
+/// exchange lands. `BatchSortAgg` builds its total-phase `LogicalAgg` directly over its merge
+/// exchange and does not consult the strategy.
+pub fn build_total_agg(
+    total_agg_calls: Vec<PlanAggCall>,
+    group_keys: Vec<usize>,
+    exchange: PlanRef,
+) -> LogicalAgg {
+    LogicalAgg::new(total_agg_calls, group_keys, exchange)
+}
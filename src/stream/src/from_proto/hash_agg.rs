@@ -13,6 +13,21 @@
 // limitations under the License.
 
 //! Global Streaming Hash Aggregators
+//!
+//! For a high-cardinality `GROUP BY`, keeping every group's aggregation state resident is
+//! unbounded. The dispatcher builds a [`GroupEvictionMap`] keyed by the concrete `K: HashKey` it
+//! resolves to (see `HashAggExecutorDispatcher::dispatch`) and hands it to `HashAggExecutor` (see
+//! `crate::executor::aggregation`), which spills cold groups to its `RowBasedStateTable`s once
+//! the map crosses `spill_memory_budget_bytes`, using the map's clock/second-chance policy to
+//! pick eviction candidates and a read-modify-write against the state table to reload (or
+//! re-create) a group's state the next time a row for it arrives, so an evicted-then-reactivated
+//! group still produces the correct incremental delta.
+//!
+//! With `enable_dirty_group_tracking`, the dispatcher also builds a [`DirtyGroupSet`] keyed the
+//! same way. Flush then skips re-deriving groups that weren't touched in the epoch: only the
+//! keys `DirtyGroupSet::drain_dirty` returns are read against the state table and emitted as
+//! `StreamChunk` deltas. A group that's both dirty and chosen for eviction is flushed first, so
+//! its delta isn't lost to the spill path racing the barrier.
 
 use std::marker::PhantomData;
 
@@ -23,6 +38,9 @@ use super::agg_call::build_agg_call_from_prost;
 use super::*;
 use crate::executor::aggregation::{generate_state_tables_from_proto, AggCall};
 use crate::executor::{ActorContextRef, HashAggExecutor, PkIndices};
+use crate::task::dirty_group::DirtyGroupSet;
+use crate::task::group_eviction::GroupEvictionMap;
+use crate::task::stable_hasher::StableHasherBuilder;
 use crate::task::ActorId;
 
 pub struct HashAggExecutorDispatcher<S: StateStore>(PhantomData<S>);
@@ -37,6 +55,18 @@ pub struct HashAggExecutorDispatcherArgs<S: StateStore> {
     executor_id: u64,
     state_tables: Vec<RowBasedStateTable<S>>,
     state_table_col_mappings: Vec<Vec<usize>>,
+    /// Soft cap, in bytes, on the in-memory group-aggregation state before cold groups are
+    /// spilled to `state_tables` and evicted from the live map. `0` means unbounded (the old
+    /// always-resident behavior), for deployments where the group cardinality is known to be
+    /// small.
+    spill_memory_budget_bytes: usize,
+    /// When set, flush tracks an explicit dirty-group set (keys touched since the last barrier)
+    /// and only re-derives/emits deltas for those groups, instead of scanning every resident
+    /// group on each barrier.
+    enable_dirty_group_tracking: bool,
+    /// Hashes the group key to a vnode through `StableHasher` rather than whatever fast hasher
+    /// happens to be in scope, so the same key always routes to the same vnode across builds.
+    hash_builder: StableHasherBuilder,
 }
 
 impl<S: StateStore> HashKeyDispatcher for HashAggExecutorDispatcher<S> {
@@ -44,6 +74,20 @@ impl<S: StateStore> HashKeyDispatcher for HashAggExecutorDispatcher<S> {
     type Output = Result<BoxedExecutor>;
 
     fn dispatch<K: HashKey>(args: Self::Input) -> Self::Output {
+        // Built here, at the one spot `K` is concretely bound, rather than threading the raw
+        // budget through to `HashAggExecutor` and leaving it to build its own map: the
+        // eviction map's key type has to match the `HashKey` this dispatch resolved to. `V` is
+        // the group's encoded accumulator state (whatever byte form `HashAggExecutor`'s state
+        // representation serializes to) -- this map only needs to track size/recency/eviction
+        // order over it, not interpret it.
+        let eviction: GroupEvictionMap<K, Vec<u8>> =
+            GroupEvictionMap::new(args.spill_memory_budget_bytes);
+        // Same reasoning as `eviction`: built here so its key type matches `K`, instead of
+        // threading the bare `enable_dirty_group_tracking` flag down to `HashAggExecutor` and
+        // leaving it to allocate its own set.
+        let dirty_groups: Option<DirtyGroupSet<K>> =
+            args.enable_dirty_group_tracking.then(DirtyGroupSet::new);
+
         Ok(HashAggExecutor::<K, S>::new(
             args.ctx,
             args.input,
@@ -54,6 +98,9 @@ impl<S: StateStore> HashKeyDispatcher for HashAggExecutorDispatcher<S> {
             args.key_indices,
             args.state_tables,
             args.state_table_col_mappings,
+            eviction,
+            dirty_groups,
+            args.hash_builder,
         )?
         .boxed())
     }
@@ -66,7 +113,7 @@ impl ExecutorBuilder for HashAggExecutorBuilder {
         params: ExecutorParams,
         node: &StreamNode,
         store: impl StateStore,
-        _stream: &mut LocalStreamManagerCore,
+        stream: &mut LocalStreamManagerCore,
     ) -> Result<BoxedExecutor> {
         let node = try_match_expand!(node.get_node_body().unwrap(), NodeBody::HashAgg)?;
         let key_indices = node
@@ -90,11 +137,21 @@ impl ExecutorBuilder for HashAggExecutorBuilder {
             .map(|idx| input.schema().fields[*idx].data_type())
             .collect_vec();
         let kind = calc_hash_key_kind(&keys);
+        // `StableHasher` (SipHash-2-4, fixed key) gives the same output across versions, unlike a
+        // fast hasher -- see `crate::task::stable_hasher` for why that matters for vnode routing.
+        // `HashKey` itself lives in `risingwave_common::hash`, outside this crate, and its own
+        // vnode computation is not changed here: this only hands `HashAggExecutor` a concrete
+        // `StableHasherBuilder` instead of leaving it to default to whatever `BuildHasher` it
+        // would otherwise pick.
+        let hash_builder = StableHasherBuilder;
 
         let vnodes = params.vnode_bitmap.expect("vnodes not set for hash agg");
         let state_tables =
             generate_state_tables_from_proto(store, &node.internal_tables, Some(vnodes.into()));
 
+        let spill_memory_budget_bytes = stream.config.hash_agg_spill_memory_budget_bytes();
+        let enable_dirty_group_tracking = stream.config.enable_hash_agg_dirty_group_tracking();
+
         let args = HashAggExecutorDispatcherArgs {
             ctx: params.actor_context,
             input,
@@ -105,6 +162,9 @@ impl ExecutorBuilder for HashAggExecutorBuilder {
             executor_id: params.executor_id,
             state_tables,
             state_table_col_mappings,
+            spill_memory_budget_bytes,
+            enable_dirty_group_tracking,
+            hash_builder,
         };
         HashAggExecutorDispatcher::dispatch_by_kind(kind, args)
     }
@@ -0,0 +1,148 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Credit/debt accounting for inter-actor output channels.
+//!
+//! The bounded `mpsc` behind every `ConsumableChannelPair` gives all-or-nothing backpressure: once
+//! the fixed `LOCAL_OUTPUT_CHANNEL_SIZE` capacity fills up, the sender blocks with no visibility
+//! into which downstream is actually slow. This module tracks outstanding un-acked bytes per edge
+//! so one slow consumer throttles exactly its upstream rather than making channel capacity opaque.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+use risingwave_common::config::StreamingConfig;
+
+use crate::task::UpDownActorIds;
+
+impl BackpressureWatermarks {
+    pub fn from_config(config: &StreamingConfig) -> Self {
+        Self {
+            high: config.backpressure_high_watermark_bytes(),
+            low: config.backpressure_low_watermark_bytes(),
+        }
+    }
+}
+
+/// High/low watermarks (in bytes) governing when a sender should start and stop throttling on an
+/// edge. Configured via `StreamingConfig`.
+#[derive(Debug, Clone, Copy)]
+pub struct BackpressureWatermarks {
+    pub high: i64,
+    pub low: i64,
+}
+
+impl Default for BackpressureWatermarks {
+    fn default() -> Self {
+        Self {
+            high: 64 * 1024 * 1024,
+            low: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Outstanding un-acked debt for one `(upstream, downstream)` edge. The upstream adds debt as it
+/// sends records, the downstream executor returns credit as it consumes them.
+#[derive(Debug)]
+struct EdgeDebt {
+    debt_bytes: AtomicI64,
+    notify: Notify,
+}
+
+/// Tracks per-edge debt across all local channels so a sender can block once its edge's debt
+/// exceeds the high watermark, and `StreamingMetrics` can expose a per-edge backpressure gauge.
+#[derive(Debug, Default)]
+pub struct BackpressureTracker {
+    edges: parking_lot::Mutex<HashMap<UpDownActorIds, Arc<EdgeDebt>>>,
+    watermarks: BackpressureWatermarks,
+}
+
+impl BackpressureTracker {
+    pub fn new(watermarks: BackpressureWatermarks) -> Self {
+        Self {
+            edges: Default::default(),
+            watermarks,
+        }
+    }
+
+    fn edge(&self, ids: UpDownActorIds) -> Arc<EdgeDebt> {
+        self.edges
+            .lock()
+            .entry(ids)
+            .or_insert_with(|| {
+                Arc::new(EdgeDebt {
+                    debt_bytes: AtomicI64::new(0),
+                    notify: Notify::new(),
+                })
+            })
+            .clone()
+    }
+
+    /// Called by the sender after pushing `bytes` worth of records onto the edge. If this pushes
+    /// debt above the high watermark, future sends on this edge should await
+    /// [`Self::wait_for_credit`] before sending more.
+    pub fn add_debt(&self, ids: UpDownActorIds, bytes: i64) {
+        self.edge(ids).debt_bytes.fetch_add(bytes, Ordering::SeqCst);
+    }
+
+    /// Called by the downstream executor as it consumes records, returning credit to the sender.
+    pub fn return_credit(&self, ids: UpDownActorIds, bytes: i64) {
+        let edge = self.edge(ids);
+        let prev = edge.debt_bytes.fetch_sub(bytes, Ordering::SeqCst);
+        if prev - bytes <= self.watermarks.low {
+            edge.notify.notify_waiters();
+        }
+    }
+
+    /// The current outstanding debt on an edge, exposed for the per-edge backpressure gauge.
+    pub fn debt(&self, ids: UpDownActorIds) -> i64 {
+        self.edges
+            .lock()
+            .get(&ids)
+            .map(|e| e.debt_bytes.load(Ordering::SeqCst))
+            .unwrap_or(0)
+    }
+
+    /// Blocks until the edge's debt drops back to (or below) the low watermark, if it is
+    /// currently above the high watermark. A no-op if the edge isn't currently congested.
+    pub async fn wait_for_credit(&self, ids: UpDownActorIds) {
+        let edge = self.edge(ids);
+        loop {
+            // Register for the next notification *before* checking the condition: if `notified()`
+            // were called only after the load, a `return_credit` landing between the load and the
+            // await would call `notify_waiters()` while we're not yet registered, and since that's
+            // a wake-currently-registered-waiters-only call (not `notify_one`), the wakeup would
+            // be lost forever -- nothing else ever calls `return_credit` again once the downstream
+            // has drained everything in flight.
+            let notified = edge.notify.notified();
+            if edge.debt_bytes.load(Ordering::SeqCst) <= self.watermarks.high {
+                break;
+            }
+            notified.await;
+        }
+    }
+
+    pub fn remove_edge(&self, ids: UpDownActorIds) {
+        self.edges.lock().remove(&ids);
+    }
+
+    /// Drops debt tracking for every edge not satisfying `f`, mirroring
+    /// `SharedContext::retain`'s cleanup when an actor is dropped.
+    pub fn retain(&self, f: impl Fn(&UpDownActorIds) -> bool) {
+        self.edges.lock().retain(|ids, _| f(ids));
+    }
+}
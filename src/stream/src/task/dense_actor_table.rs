@@ -0,0 +1,101 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A cache-line-friendly re-layout of the actor graph, built once `update_actors` has finished a
+//! batch and the graph has no more pending chain edges. `self.actors`/the channel map key
+//! everything by `ActorId` in a `HashMap`, so every dispatch hop chases a pointer through a hash
+//! lookup; this compacts the *resolved* portion of the graph into parallel `Vec`s indexed by a
+//! small dense [`LocalIdx`]. Dispatcher construction reads downstream ids from it; the
+//! steady-state per-message send loop does not (see [`DenseActorTable::downstream`]). The
+//! `HashMap<ActorId, LocalIdx>` only has to be walked on this cold finalize path.
+
+use std::collections::HashMap;
+
+use crate::task::ActorId;
+
+/// A dense index into a [`DenseActorTable`], stable only for the lifetime of that table (a new
+/// finalize pass may reassign indices).
+pub type LocalIdx = u32;
+
+/// The finalized, contiguous view of one compute node's actor graph.
+#[derive(Debug, Default, Clone)]
+pub struct DenseActorTable {
+    /// `actor_ids[idx]` is the `ActorId` at dense index `idx`.
+    actor_ids: Vec<ActorId>,
+    /// Cold-path lookup from `ActorId` to its dense index, used only to build
+    /// `resolved_downstream` and to translate ids coming in from outside (barriers, RPCs).
+    index_of: HashMap<ActorId, LocalIdx>,
+    /// `resolved_downstream[idx]` is the list of dense indices this actor's dispatcher(s) send
+    /// to, for downstream actors that are themselves local to this table.
+    resolved_downstream: Vec<Vec<LocalIdx>>,
+}
+
+impl DenseActorTable {
+    /// Builds a table from every actor id known to this node and the resolved `(upstream,
+    /// downstream)` dispatcher edges between them. Called once per `update_actors` batch, after
+    /// `pending_chain_edges` is empty (i.e. the graph is known-complete), so the remapping doesn't
+    /// have to be redone on every message.
+    pub fn build(actor_ids: &[ActorId], resolved_edges: &[(ActorId, ActorId)]) -> Self {
+        let actor_ids = actor_ids.to_vec();
+        let index_of: HashMap<ActorId, LocalIdx> = actor_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (*id, idx as LocalIdx))
+            .collect();
+
+        let mut resolved_downstream = vec![Vec::new(); actor_ids.len()];
+        for (up_id, down_id) in resolved_edges {
+            if let (Some(&up_idx), Some(&down_idx)) = (index_of.get(up_id), index_of.get(down_id))
+            {
+                resolved_downstream[up_idx as usize].push(down_idx);
+            }
+        }
+
+        Self {
+            actor_ids,
+            index_of,
+            resolved_downstream,
+        }
+    }
+
+    /// Translates an `ActorId` to its dense index, for the cold setup path (e.g. mapping a
+    /// barrier's actor id list once before the hot dispatch loop runs).
+    pub fn local_idx(&self, actor_id: ActorId) -> Option<LocalIdx> {
+        self.index_of.get(&actor_id).copied()
+    }
+
+    pub fn actor_id(&self, idx: LocalIdx) -> Option<ActorId> {
+        self.actor_ids.get(idx as usize).copied()
+    }
+
+    /// The dense indices of `idx`'s resolved local downstream actors. `LocalStreamManagerCore::
+    /// resolve_downstream_ids` already indexes into this for dispatcher construction; the
+    /// steady-state per-message send loop still looks up `SharedContext`'s channel map instead,
+    /// since that loop lives in the dispatch executors (`crate::executor::dispatch`), outside
+    /// this module.
+    pub fn downstream(&self, idx: LocalIdx) -> &[LocalIdx] {
+        self.resolved_downstream
+            .get(idx as usize)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn len(&self) -> usize {
+        self.actor_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actor_ids.is_empty()
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracks which group keys a `HashAggExecutor` (see `crate::from_proto::hash_agg`) has actually
+//! seen input rows for since the last barrier, so flush can re-derive and emit deltas for only
+//! those groups instead of scanning every resident group on each epoch.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A per-epoch set of "touched since last barrier" group keys. `mark_dirty` is called as rows
+/// arrive; `drain_dirty` is called once per barrier by the flush path, which both returns and
+/// clears the set so the next epoch starts clean.
+#[derive(Debug, Default)]
+pub struct DirtyGroupSet<K> {
+    dirty: HashSet<K>,
+}
+
+impl<K: Eq + Hash + Clone> DirtyGroupSet<K> {
+    pub fn new() -> Self {
+        Self {
+            dirty: HashSet::new(),
+        }
+    }
+
+    pub fn mark_dirty(&mut self, key: K) {
+        self.dirty.insert(key);
+    }
+
+    pub fn is_dirty(&self, key: &K) -> bool {
+        self.dirty.contains(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.dirty.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dirty.is_empty()
+    }
+
+    /// Returns every key marked dirty since the last drain, and clears the set for the next
+    /// epoch. Called once per barrier by the flush path; a key that's both dirty and chosen for
+    /// eviction (see `crate::task::group_eviction::GroupEvictionMap`) must be flushed using this
+    /// drained set *before* the eviction sweep runs, or its delta for this epoch is lost.
+    pub fn drain_dirty(&mut self) -> HashSet<K> {
+        std::mem::take(&mut self.dirty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain_returns_exactly_the_marked_keys() {
+        let mut set = DirtyGroupSet::new();
+        set.mark_dirty(1);
+        set.mark_dirty(2);
+        set.mark_dirty(1); // duplicate mark for an already-dirty key
+        let drained = set.drain_dirty();
+        assert_eq!(drained, HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn drain_clears_the_set_for_the_next_epoch() {
+        let mut set = DirtyGroupSet::new();
+        set.mark_dirty("a");
+        let first = set.drain_dirty();
+        assert_eq!(first, HashSet::from(["a"]));
+        assert!(set.is_empty());
+
+        // Nothing touched this epoch -> nothing dirty.
+        let second = set.drain_dirty();
+        assert!(second.is_empty());
+
+        set.mark_dirty("b");
+        let third = set.drain_dirty();
+        assert_eq!(third, HashSet::from(["b"]));
+    }
+
+    #[test]
+    fn is_dirty_reflects_current_epoch_only() {
+        let mut set = DirtyGroupSet::new();
+        set.mark_dirty(42);
+        assert!(set.is_dirty(&42));
+        assert!(!set.is_dirty(&7));
+        set.drain_dirty();
+        assert!(!set.is_dirty(&42));
+    }
+}
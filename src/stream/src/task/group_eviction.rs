@@ -0,0 +1,201 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bounded, clock/second-chance eviction map, for keeping `HashAggExecutor`'s (see
+//! `crate::from_proto::hash_agg`) in-memory group-aggregation state under a soft memory budget
+//! without paying for a full LRU's per-access list manipulation.
+//!
+//! Each entry tracks its own `size_bytes` rather than assuming a uniform per-entry cost, since
+//! aggregation states for different groups (e.g. a group with a `STRING_AGG` vs a bare `COUNT`)
+//! can differ wildly in size. Eviction only runs once `current_bytes` crosses `budget_bytes`
+//! (`budget_bytes == 0` means unbounded, matching the old always-resident behavior), and is the
+//! caller's job to invoke (e.g. after inserting or growing an entry) -- this type does no
+//! background work of its own.
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+struct Entry<V> {
+    value: V,
+    size_bytes: usize,
+    /// Set on every access, cleared (rather than evicted) the first time the clock hand passes
+    /// over it -- the "second chance" that distinguishes this from plain FIFO eviction.
+    referenced: bool,
+}
+
+/// A clock/second-chance eviction map keyed by `K`, with explicit per-entry byte accounting
+/// against a soft `budget_bytes` cap.
+pub struct GroupEvictionMap<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    /// Clock order. A stale entry left behind by a `remove` is skipped when encountered, unless the
+    /// key has been re-inserted since: the re-insert queues a second copy, and the hand then
+    /// treats both copies as the live key.
+    clock: VecDeque<K>,
+    current_bytes: usize,
+    budget_bytes: usize,
+}
+
+impl<K: Eq + Hash + Clone, V> GroupEvictionMap<K, V> {
+    /// `budget_bytes == 0` disables eviction entirely (unbounded, always-resident).
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            clock: VecDeque::new(),
+            current_bytes: 0,
+            budget_bytes,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn current_bytes(&self) -> usize {
+        self.current_bytes
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Marks `key` referenced (protecting it from the next clock sweep) and returns its value.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let entry = self.entries.get_mut(key)?;
+        entry.referenced = true;
+        Some(&entry.value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let entry = self.entries.get_mut(key)?;
+        entry.referenced = true;
+        Some(&mut entry.value)
+    }
+
+    /// Inserts or replaces `key`'s entry. Does not evict by itself -- call [`Self::evict`]
+    /// afterwards (typically the caller does this once per barrier, or after every insert if it
+    /// wants to enforce the budget eagerly).
+    pub fn insert(&mut self, key: K, value: V, size_bytes: usize) {
+        if let Some(old) = self.entries.get(&key) {
+            self.current_bytes -= old.size_bytes;
+        } else {
+            self.clock.push_back(key.clone());
+        }
+        self.current_bytes += size_bytes;
+        // Starts unreferenced: a group that's inserted and never looked up again before the next
+        // sweep (e.g. a one-off key) shouldn't get a free pass just for being new.
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                size_bytes,
+                referenced: false,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.current_bytes -= entry.size_bytes;
+        Some(entry.value)
+    }
+
+    /// Runs the clock hand until `current_bytes` is back at or under `budget_bytes`, returning
+    /// the evicted `(key, value)` pairs in eviction order so the caller can persist them (e.g.
+    /// flush to `RowBasedStateTable`) before they're dropped. A referenced entry is given a
+    /// second chance (flag cleared, requeued) instead of being evicted on the first pass.
+    pub fn evict(&mut self) -> Vec<(K, V)> {
+        let mut evicted = Vec::new();
+        if self.budget_bytes == 0 {
+            return evicted;
+        }
+        // Bounded by `2 * clock.len()`: every live entry gets at most one second chance before
+        // it must be evicted, so this always terminates even if everything is referenced.
+        let mut spins_left = self.clock.len().saturating_mul(2).max(1);
+        while self.current_bytes > self.budget_bytes && spins_left > 0 {
+            spins_left -= 1;
+            let Some(key) = self.clock.pop_front() else {
+                break;
+            };
+            let Some(entry) = self.entries.get_mut(&key) else {
+                // Stale: the key was removed since it was queued.
+                continue;
+            };
+            if entry.referenced {
+                entry.referenced = false;
+                self.clock.push_back(key);
+                continue;
+            }
+            let entry = self.entries.remove(&key).unwrap();
+            self.current_bytes -= entry.size_bytes;
+            evicted.push((key, entry.value));
+        }
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_under_budget_does_not_evict() {
+        let mut map = GroupEvictionMap::new(1024);
+        map.insert("a", 1, 100);
+        map.insert("b", 2, 100);
+        assert!(map.evict().is_empty());
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.current_bytes(), 200);
+    }
+
+    #[test]
+    fn eviction_triggers_at_tiny_budget() {
+        let mut map = GroupEvictionMap::new(150);
+        map.insert("a", 1, 100);
+        map.insert("b", 2, 100);
+        // Over budget (200 > 150); neither was read since insert, so the first one the clock
+        // hand reaches is evicted immediately with no second chance.
+        let evicted = map.evict();
+        assert_eq!(evicted.len(), 1);
+        assert!(map.current_bytes() <= 150);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn get_marks_referenced_and_protects_from_eviction() {
+        let mut map = GroupEvictionMap::new(100);
+        map.insert("a", 1, 60);
+        map.insert("b", 2, 60);
+        // Touch "a" again so its referenced flag is fresh going into the sweep; "b" is the only
+        // unreferenced-after-one-pass entry and should be the one evicted.
+        assert_eq!(map.get(&"a"), Some(&1));
+        let evicted = map.evict();
+        assert_eq!(evicted, vec![("b", 2)]);
+        assert!(map.contains_key(&"a"));
+        assert!(!map.contains_key(&"b"));
+    }
+
+    #[test]
+    fn unbounded_budget_never_evicts() {
+        let mut map = GroupEvictionMap::new(0);
+        for i in 0..100 {
+            map.insert(i, i, 1_000_000);
+        }
+        assert!(map.evict().is_empty());
+        assert_eq!(map.len(), 100);
+    }
+}
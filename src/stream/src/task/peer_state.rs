@@ -0,0 +1,141 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Caches the latest known health and capabilities of each remote compute node, so remote-actor
+//! wiring can consult a peer's liveness instead of discovering it's dead only when an RPC times
+//! out.
+
+use std::time::{Duration, Instant};
+
+use risingwave_common::util::addr::HostAddr;
+
+/// How long a peer stays in the fail-fast `connected: false` state before `is_connected` reopens
+/// a retry window on its own. Without this, a peer marked disconnected could never be dialed
+/// again unless something happened to call `note_heartbeat`/`note_reconnected` for it first --
+/// and nothing drives that call on a channel that's permanently deferred to `pending_chain_edges`
+/// precisely because the peer looks disconnected. This bounds that deadlock.
+const RECONNECT_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// The negotiated protocol/feature version and liveness of one remote compute node, as last
+/// observed by this worker.
+#[derive(Debug, Clone)]
+pub struct PeerState {
+    /// Feature/protocol version negotiated with this peer, used to pick feature-gated exchange
+    /// formats per peer.
+    pub protocol_version: u32,
+    pub last_heartbeat_at: Instant,
+    pub connected: bool,
+    /// When this peer was last marked disconnected, so a retry can be attempted again after
+    /// [`RECONNECT_COOLDOWN`] even if no explicit heartbeat/reconnect ever arrives for it.
+    pub disconnected_at: Option<Instant>,
+}
+
+impl PeerState {
+    pub fn new(protocol_version: u32) -> Self {
+        Self {
+            protocol_version,
+            last_heartbeat_at: Instant::now(),
+            connected: true,
+            disconnected_at: None,
+        }
+    }
+
+    pub fn on_heartbeat(&mut self) {
+        self.last_heartbeat_at = Instant::now();
+        self.connected = true;
+        self.disconnected_at = None;
+    }
+
+    pub fn mark_disconnected(&mut self) {
+        self.connected = false;
+        self.disconnected_at = Some(Instant::now());
+    }
+
+    /// Whether a dial to this peer should be attempted: either we believe it's connected, or it's
+    /// been long enough since it was marked disconnected that it's worth retrying.
+    pub fn is_retriable(&self) -> bool {
+        self.connected
+            || self
+                .disconnected_at
+                .map_or(true, |at| at.elapsed() >= RECONNECT_COOLDOWN)
+    }
+}
+
+/// `HostAddr`-keyed cache of [`PeerState`]s, consulted by `build_channel_for_chain_node` and the
+/// `ActorInfo { host: Some(..) }` path so remote channels to a known-dead node fail fast (or
+/// buffer) instead of blindly dialing.
+#[derive(Debug, Default)]
+pub struct PeerStates {
+    states: parking_lot::Mutex<std::collections::HashMap<HostAddr, PeerState>>,
+}
+
+impl PeerStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn note_heartbeat(&self, addr: HostAddr, protocol_version: u32) {
+        let mut states = self.states.lock();
+        states
+            .entry(addr)
+            .and_modify(|s| {
+                s.on_heartbeat();
+                s.protocol_version = protocol_version;
+            })
+            .or_insert_with(|| PeerState::new(protocol_version));
+    }
+
+    /// Records a successful connection to `addr` outside of the heartbeat RPC path (e.g. a
+    /// `RemoteInput` forwarder that just finished dialing), clearing any prior disconnect without
+    /// requiring a renegotiated protocol version. Keeps the previously-known version if the peer
+    /// was seen before, otherwise assumes version `0`.
+    pub fn note_reconnected(&self, addr: &HostAddr) {
+        let mut states = self.states.lock();
+        match states.get_mut(addr) {
+            Some(state) => state.on_heartbeat(),
+            None => {
+                states.insert(addr.clone(), PeerState::new(0));
+            }
+        }
+    }
+
+    pub fn mark_disconnected(&self, addr: &HostAddr) {
+        // Insert a disconnected entry when `addr` has never been seen before, mirroring
+        // `note_heartbeat`'s `entry().or_insert_with(...)`: otherwise the very first dial failure
+        // against a peer we've never heartbeat-ed or reconnected to would be dropped on the
+        // floor, `disconnected_at` would never be recorded, and `RECONNECT_COOLDOWN` would never
+        // engage -- callers would just hot-loop retrying it immediately.
+        let mut states = self.states.lock();
+        match states.get_mut(addr) {
+            Some(state) => state.mark_disconnected(),
+            None => {
+                let mut state = PeerState::new(0);
+                state.mark_disconnected();
+                states.insert(addr.clone(), state);
+            }
+        }
+    }
+
+    /// Whether a channel to `addr` should be attempted: connected, never seen before, or its
+    /// disconnect cooldown has elapsed (see [`PeerState::is_retriable`]), so a peer marked
+    /// disconnected isn't rejected forever just because nothing ever called `note_heartbeat` or
+    /// `note_reconnected` for it again.
+    pub fn is_connected(&self, addr: &HostAddr) -> bool {
+        self.states
+            .lock()
+            .get(addr)
+            .map(|s| s.is_retriable())
+            .unwrap_or(true)
+    }
+}
@@ -14,7 +14,9 @@
 
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
 
+use bytes::Bytes;
 use futures::channel::mpsc::{channel, Receiver};
 use itertools::Itertools;
 use madsim::collections::{HashMap, HashSet};
@@ -32,6 +34,12 @@ use risingwave_rpc_client::ComputeClientPool;
 use risingwave_storage::{dispatch_state_store, StateStore, StateStoreImpl};
 use tokio::sync::oneshot;
 
+use super::backpressure::{BackpressureTracker, BackpressureWatermarks};
+use super::dense_actor_table::DenseActorTable;
+use super::peer_state::PeerStates;
+use super::supervisor::{ActorExitStatus, ActorStatus, Backoff, RestartPolicy, Supervisor};
+use super::topology_snapshot::TopologySnapshot;
+use super::upstream_availability::{UpstreamAvailability, UpstreamUnavailable};
 use super::{unique_executor_id, unique_operator_id, CollectResult};
 use crate::executor::dispatch::*;
 use crate::executor::merge::RemoteInput;
@@ -82,11 +90,62 @@ pub struct LocalStreamManagerCore {
 
     /// Config of streaming engine
     pub(crate) config: StreamingConfig,
+
+    /// Per-actor restart bookkeeping (backoff state, restart count, last error), consulted by
+    /// `run_supervised_actor` whenever a spawned actor's `run()` future returns.
+    supervisors: HashMap<ActorId, Supervisor>,
+
+    /// Terminal status of actors that have stopped being restarted, recorded by each actor's
+    /// `exit_hook` rather than by panicking the worker.
+    exit_statuses: Arc<Mutex<HashMap<ActorId, ActorExitStatus>>>,
+
+    /// Per-edge credit/debt accounting layered on top of the bounded local channels, so a single
+    /// slow downstream throttles exactly its own upstream edge.
+    pub(crate) backpressure: Arc<BackpressureTracker>,
+
+    /// Join handles of tasks spawned on behalf of an actor (currently just its `RemoteInput`
+    /// forwarders) that should be aborted together with the actor's own handle, rather than left
+    /// to run until they error out on a closed sender.
+    linked_tasks: HashMap<ActorId, Vec<JoinHandle<()>>>,
+
+    /// Live status of each actor, updated as it moves through its lifecycle. Exposed via
+    /// `LocalStreamManager::actor_statuses` for cluster-wide health polling.
+    actor_statuses: Arc<Mutex<HashMap<ActorId, ActorStatus>>>,
+
+    /// Chain-node upstream edges that couldn't be wired up on a previous `update_actors` call
+    /// because the upstream wasn't registered yet or its host was disconnected, keyed by the
+    /// downstream actor. Retried the next time `update_actors` runs.
+    pending_chain_edges: HashMap<ActorId, Vec<ActorId>>,
+
+    /// Liveness and negotiated protocol version of remote compute nodes we have actors talking
+    /// to, so a channel to a known-dead peer can fail fast (or be deferred as pending) instead of
+    /// blocking on a doomed RPC.
+    pub(crate) peer_states: Arc<PeerStates>,
+
+    /// The `StreamActor::revision` last applied for each actor, mirroring Sentry Relay's
+    /// revision-gated config sync: a re-push of an unchanged actor carries the same revision, so
+    /// `update_actors` can skip rebuilding its channels instead of relying on actor-id presence
+    /// alone (which can't tell "unchanged" apart from "meta pushed a new plan under the same id").
+    applied_revisions: HashMap<ActorId, u64>,
+
+    /// The dense, cache-line-friendly re-layout of the actor graph, rebuilt by
+    /// `finalize_dense_layout` once an `update_actors` batch leaves no pending chain edges behind.
+    /// `None` whenever the graph is mid-flight (some chain edge is still pending), since the
+    /// resolved-downstream lists would otherwise be stale.
+    dense_actors: Option<DenseActorTable>,
+
+    /// Actor ids this node has itself dropped via `drop_actor`/`drop_all_actors`. Unlike
+    /// `actor_infos` (which only records what's been broadcast so far, additively, and so can't
+    /// tell "retired" apart from "not broadcast yet"), a ours-to-drop id is a definitive, locally
+    /// known "this is gone for good" signal, used by `classify_missing_upstream` to fail a chain
+    /// node's build outright instead of retrying a reference to an actor we ourselves tore down.
+    /// Actor ids are never reused, so this only grows with genuinely new actors, not churn.
+    retired_actor_ids: HashSet<ActorId>,
 }
 
 /// `LocalStreamManager` manages all stream executors in this project.
 pub struct LocalStreamManager {
-    core: Mutex<LocalStreamManagerCore>,
+    core: Arc<Mutex<LocalStreamManagerCore>>,
 }
 
 pub struct ExecutorParams {
@@ -129,7 +188,7 @@ impl Debug for ExecutorParams {
 impl LocalStreamManager {
     fn with_core(core: LocalStreamManagerCore) -> Self {
         Self {
-            core: Mutex::new(core),
+            core: Arc::new(Mutex::new(core)),
         }
     }
 
@@ -176,10 +235,23 @@ impl LocalStreamManager {
         actor_ids_to_collect: impl IntoIterator<Item = ActorId>,
         need_sync: bool,
     ) -> Result<CollectResult> {
-        let rx = self.send_barrier(barrier, actor_ids_to_send, actor_ids_to_collect)?;
-
-        // Wait for all actors finishing this barrier.
-        let mut collect_result = rx.await.unwrap();
+        let actor_ids_to_collect: Vec<ActorId> = actor_ids_to_collect.into_iter().collect();
+        let exit_statuses = self.core.lock().exit_statuses.clone();
+        let rx = self.send_barrier(barrier, actor_ids_to_send, actor_ids_to_collect.iter().copied())?;
+
+        // An actor that permanently stopped (restart policy exhausted, or `Never`) never sends
+        // its collect-ack, so `rx` alone would hang forever instead of ever resolving. Race it
+        // against `exit_statuses` for the actors we're waiting on, so a terminally failed actor
+        // fails this barrier instead of wedging the barrier manager.
+        let mut collect_result = tokio::select! {
+            result = rx => result.unwrap(),
+            (actor_id, status) = Self::wait_for_actor_exit(&exit_statuses, &actor_ids_to_collect) => {
+                return Err(RwError::from(ErrorCode::InternalError(format!(
+                    "actor {} exited before barrier {:?} at epoch {} could be collected: {:?}",
+                    actor_id, barrier, barrier.epoch.prev, status
+                ))));
+            }
+        };
 
         // Sync states from shared buffer to S3 before telling meta service we've done.
         if need_sync {
@@ -189,12 +261,14 @@ impl LocalStreamManager {
                         collect_result.synced_sstables =
                             store.get_uncommitted_ssts(barrier.epoch.prev);
                     }
-                    // TODO: Handle sync failure by propagating it
-                    // back to global barrier manager
-                    Err(e) => panic!(
-                        "Failed to sync state store after receiving barrier {:?} due to {}",
-                        barrier, e
-                    ),
+                    // Surface the failure to the caller (the global barrier manager) instead of
+                    // taking the whole compute node down, so meta can trigger recovery.
+                    Err(e) => {
+                        return Err(RwError::from(ErrorCode::InternalError(format!(
+                            "failed to sync state store after receiving barrier {:?} at epoch {}: {}",
+                            barrier, barrier.epoch.prev, e
+                        ))));
+                    }
                 }
             });
         }
@@ -202,6 +276,26 @@ impl LocalStreamManager {
         Ok(collect_result)
     }
 
+    /// Polls `exit_statuses` until one of `actor_ids_to_collect` has a terminal entry, returning
+    /// it. Used to race against the barrier-collect `rx` in [`Self::send_and_collect_barrier`],
+    /// since a permanently failed actor never sends its collect-ack.
+    async fn wait_for_actor_exit(
+        exit_statuses: &Arc<Mutex<HashMap<ActorId, ActorExitStatus>>>,
+        actor_ids_to_collect: &[ActorId],
+    ) -> (ActorId, ActorExitStatus) {
+        loop {
+            {
+                let statuses = exit_statuses.lock();
+                for actor_id in actor_ids_to_collect {
+                    if let Some(status) = statuses.get(actor_id) {
+                        return (*actor_id, status.clone());
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
     /// Broadcast a barrier to all senders. Returns immediately, and caller won't be notified when
     /// this barrier is finished.
     #[cfg(test)]
@@ -253,11 +347,14 @@ impl LocalStreamManager {
         core.context.take_receiver(&ids)
     }
 
+    /// Returns `true` if some upstream couldn't be resolved yet and was recorded for retry; the
+    /// caller may re-drive the build later once the peer reconnects or the remaining actors
+    /// arrive, rather than treating this as a hard failure.
     pub fn update_actors(
         &self,
         actors: &[stream_plan::StreamActor],
         hanging_channels: &[stream_service::HangingChannel],
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let mut core = self.core.lock();
         core.update_actors(actors, hanging_channels)
     }
@@ -293,8 +390,9 @@ impl LocalStreamManager {
     /// This function could only be called once during the lifecycle of `LocalStreamManager` for
     /// now.
     pub fn build_actors(&self, actors: &[ActorId], env: StreamEnvironment) -> Result<()> {
+        let core_ref = self.core.clone();
         let mut core = self.core.lock();
-        core.build_actors(actors, env)
+        core.build_actors(actors, env, core_ref)
     }
 
     #[cfg(test)]
@@ -312,12 +410,82 @@ impl LocalStreamManager {
     pub fn state_store(&self) -> StateStoreImpl {
         self.core.lock().state_store.clone()
     }
+
+    /// Snapshot of every actor's live status, restart count, and the epoch of an
+    /// `ActorStatus::Barrier` status (`None` for every other status; barrier collection does not
+    /// set that status, see [`ActorStatus`]). Wired into the `stream_service` RPC so meta/operators can poll actor health across the
+    /// cluster instead of scraping logs.
+    pub fn actor_statuses(&self) -> Vec<(ActorId, ActorStatus, u32, Option<u64>)> {
+        let core = self.core.lock();
+        let statuses = core.actor_statuses.lock();
+        statuses
+            .iter()
+            .map(|(actor_id, status)| {
+                let restart_count = core
+                    .supervisors
+                    .get(actor_id)
+                    .map(|s| s.state().restart_count)
+                    .unwrap_or(0);
+                let last_barrier_epoch = match status {
+                    ActorStatus::Barrier { epoch } => Some(*epoch),
+                    _ => None,
+                };
+                (*actor_id, status.clone(), restart_count, last_barrier_epoch)
+            })
+            .collect()
+    }
+
+    /// A binary dump of this node's current actor/channel wiring, for post-mortem debugging.
+    /// See [`TopologySnapshot`] for the format and its round-trip caveats.
+    pub fn dump_topology(&self) -> Bytes {
+        self.core.lock().topology_snapshot().encode()
+    }
+
+    /// The current cache-line-friendly actor layout, if the graph has no pending chain edges.
+    /// `None` mid-flight, in which case callers should fall back to the `ActorId`-keyed paths.
+    pub fn dense_actors(&self) -> Option<DenseActorTable> {
+        self.core.lock().dense_actors.clone()
+    }
+
+    /// Rehydrates the `pending_chain_edges` bookkeeping from a previously-dumped snapshot, so a
+    /// restarting node doesn't have to wait on meta to re-broadcast the full plan just to know
+    /// which chain edges were still unresolved. Only the bookkeeping round-trips: the caller is
+    /// still responsible for rebuilding the actors and channels themselves via
+    /// `update_actors`/`build_actors`.
+    pub fn load_topology(&self, bytes: &[u8]) -> Result<()> {
+        let snapshot = TopologySnapshot::decode(bytes)?;
+        self.core
+            .lock()
+            .restore_pending_chain_edges(snapshot.pending_edges);
+        Ok(())
+    }
 }
 
-fn update_upstreams(context: &SharedContext, ids: &[UpDownActorIds]) {
+/// Nominal debt charged against an edge for the duration a `RemoteInput` forwarder is actively
+/// relaying on it, standing in for the per-message byte accounting a sender in the real dispatch
+/// path would otherwise add incrementally. Set to `LOCAL_OUTPUT_CHANNEL_SIZE` bytes. Debt is kept
+/// per edge and each edge has one forwarder, so charges on different edges do not add up: this
+/// pushes an edge over the high watermark only if that watermark is below this many bytes.
+const REMOTE_FORWARDER_RESERVED_BYTES: i64 = LOCAL_OUTPUT_CHANNEL_SIZE as i64;
+
+/// The backoff used between restart attempts of every supervised actor; `RestartPolicy` carries
+/// no backoff settings of its own.
+fn default_backoff() -> Backoff {
+    Backoff::new(
+        Duration::from_millis(100),
+        Duration::from_secs(30),
+        2.0,
+        Duration::from_secs(60),
+    )
+}
+
+fn update_upstreams(context: &SharedContext, backpressure: &BackpressureTracker, ids: &[UpDownActorIds]) {
     ids.iter()
         .map(|id| {
             let (tx, rx) = channel(LOCAL_OUTPUT_CHANNEL_SIZE);
+            // Register this edge's debt ledger: a new edge starts at zero, an already-tracked edge
+            // keeps its current debt. It accrues as the sender pushes records.
+            backpressure.add_debt(*id, 0);
             context.add_channel_pairs(*id, (Some(tx), Some(rx)));
         })
         .count();
@@ -351,7 +519,19 @@ impl LocalStreamManagerCore {
             state_store,
             streaming_metrics,
             compute_client_pool: ComputeClientPool::new(u64::MAX),
+            backpressure: Arc::new(BackpressureTracker::new(BackpressureWatermarks::from_config(
+                &config,
+            ))),
             config,
+            supervisors: HashMap::new(),
+            exit_statuses: Arc::new(Mutex::new(HashMap::new())),
+            linked_tasks: HashMap::new(),
+            actor_statuses: Arc::new(Mutex::new(HashMap::new())),
+            pending_chain_edges: HashMap::new(),
+            peer_states: Arc::new(PeerStates::new()),
+            applied_revisions: HashMap::new(),
+            dense_actors: None,
+            retired_actor_ids: HashSet::new(),
         }
     }
 
@@ -369,6 +549,13 @@ impl LocalStreamManagerCore {
         )
     }
 
+    /// The actor's `exit_hook`: records its terminal status instead of unwinding the process, so
+    /// the barrier manager can observe the failure on its next collection and fail the in-flight
+    /// barrier cleanly.
+    fn record_exit_status(&self, actor_id: ActorId, status: ActorExitStatus) {
+        self.exit_statuses.lock().insert(actor_id, status);
+    }
+
     fn get_actor_info(&self, actor_id: &ActorId) -> Result<&ActorInfo> {
         self.actor_infos.get(actor_id).ok_or_else(|| {
             RwError::from(ErrorCode::InternalError(
@@ -377,6 +564,39 @@ impl LocalStreamManagerCore {
         })
     }
 
+    /// Resolves the `ActorId`s a dispatcher sends to, preferring the cache-line-friendly
+    /// [`DenseActorTable`] over re-reading the raw proto field when one is available, the actor
+    /// has a single dispatcher (the dense list is the union over all of an actor's dispatchers),
+    /// and the dense list agrees on the edge count, so repeated dispatcher rebuilds (e.g. every `spawn_supervised_actor`
+    /// restart) index into dense `Vec`s instead of walking `dispatcher.downstream_actor_id`
+    /// fresh each time. This only covers dispatcher construction, not the steady-state
+    /// per-message send loop, which still goes through `SharedContext`'s channel map -- wiring
+    /// that hot path through `DenseActorTable` would require touching the dispatch executors in
+    /// `crate::executor::dispatch`, which isn't part of this module.
+    fn resolve_downstream_ids(
+        &self,
+        actor_id: ActorId,
+        dispatcher: &stream_plan::Dispatcher,
+    ) -> Vec<ActorId> {
+        let single_dispatcher = self
+            .actors
+            .get(&actor_id)
+            .map_or(false, |actor| actor.dispatcher.len() == 1);
+        if let Some(dense) = self.dense_actors.as_ref().filter(|_| single_dispatcher) {
+            if let Some(local_idx) = dense.local_idx(actor_id) {
+                let resolved: Vec<ActorId> = dense
+                    .downstream(local_idx)
+                    .iter()
+                    .filter_map(|&idx| dense.actor_id(idx))
+                    .collect();
+                if resolved.len() == dispatcher.downstream_actor_id.len() {
+                    return resolved;
+                }
+            }
+        }
+        dispatcher.downstream_actor_id.to_vec()
+    }
+
     /// Create dispatchers with downstream information registered before
     fn create_dispatcher(
         &mut self,
@@ -388,8 +608,8 @@ impl LocalStreamManagerCore {
         let mut dispatcher_impls = Vec::with_capacity(dispatchers.len());
 
         for dispatcher in dispatchers {
-            let outputs = dispatcher
-                .downstream_actor_id
+            let downstream_ids = self.resolve_downstream_ids(actor_id, dispatcher);
+            let outputs = downstream_ids
                 .iter()
                 .map(|down_id| {
                     let downstream_addr = self.get_actor_info(down_id)?.get_host()?.into();
@@ -413,7 +633,7 @@ impl LocalStreamManagerCore {
                     );
 
                     DispatcherImpl::Hash(HashDataDispatcher::new(
-                        dispatcher.downstream_actor_id.to_vec(),
+                        downstream_ids.clone(),
                         outputs,
                         column_indices,
                         hash_mapping,
@@ -540,6 +760,15 @@ impl LocalStreamManagerCore {
                 } else {
                     let upstream_addr = self.get_actor_info(up_id)?.get_host()?.into();
                     if !is_local_address(&upstream_addr, &self.context.addr) {
+                        // A peer we've already seen drop its connection isn't going to answer
+                        // any better this time; fail fast instead of burning a dial timeout.
+                        if !self.peer_states.is_connected(&upstream_addr) {
+                            return Err(RwError::from(ErrorCode::InternalError(format!(
+                                "upstream actor {} on {:?} is known to be disconnected",
+                                up_id, upstream_addr
+                            ))));
+                        }
+
                         // Get the sender for `RemoteInput` to forward received messages to
                         // receivers in `ReceiverExecutor` or
                         // `MergerExecutor`.
@@ -548,8 +777,12 @@ impl LocalStreamManagerCore {
                         let up_id = *up_id;
 
                         let pool = self.compute_client_pool.clone();
+                        let exit_statuses = self.exit_statuses.clone();
+                        let peer_states = self.peer_states.clone();
+                        let backpressure = self.backpressure.clone();
+                        let edge_ids = (up_id, actor_id);
 
-                        madsim::task::spawn(async move {
+                        let handle = madsim::task::spawn(async move {
                             let init_client = async move {
                                 let remote_input = RemoteInput::create(
                                     pool.get_client_for_addr(upstream_addr).await?,
@@ -560,13 +793,42 @@ impl LocalStreamManagerCore {
                                 Ok::<_, RwError>(remote_input)
                             };
                             match init_client.await {
-                                Ok(remote_input) => remote_input.run().await,
+                                Ok(remote_input) => {
+                                    // The dial succeeded: clear any earlier disconnect so the
+                                    // next `build_channel_for_chain_node`/`get_receive_message`
+                                    // call for this host doesn't keep fail-fasting on stale
+                                    // state (see `PeerStates::note_reconnected`).
+                                    peer_states.note_reconnected(&upstream_addr);
+                                    // Charge this edge for the forwarder's reserved buffer while
+                                    // it's actively relaying, so the per-edge debt gauge shows an
+                                    // active forwarder instead of staying at zero for the edge's
+                                    // whole life. No sender on this node awaits `wait_for_credit`
+                                    // for this edge (only a restarting actor does, for its own
+                                    // downstream edges), so the charge throttles nothing here.
+                                    // Per-message byte accounting is not done here; this is the
+                                    // coarsest unit we can charge from here.
+                                    backpressure.add_debt(edge_ids, REMOTE_FORWARDER_RESERVED_BYTES);
+                                    remote_input.run().await;
+                                    backpressure.return_credit(edge_ids, REMOTE_FORWARDER_RESERVED_BYTES);
+                                }
                                 Err(e) => {
+                                    // The peer is unreachable; evict its cached client so the
+                                    // next `get_client_for_addr` re-dials instead of reusing a
+                                    // broken connection indefinitely, and record it as disconnected
+                                    // so other actors' chain channels to it defer rather than dial.
+                                    // A dead upstream should also surface as a failure of the
+                                    // owning actor rather than just being logged and leaking the
+                                    // task.
+                                    pool.reset_client(&upstream_addr);
+                                    peer_states.mark_disconnected(&upstream_addr);
                                     error!("Spawn remote input fails:{}", e);
+                                    exit_statuses
+                                        .lock()
+                                        .insert(actor_id, ActorExitStatus::Failed(e));
                                 }
                             }
-                        })
-                        .detach();
+                        });
+                        self.linked_tasks.entry(actor_id).or_default().push(handle);
                     }
                     Ok::<_, RwError>(self.context.take_receiver(&(*up_id, actor_id))?)
                 }
@@ -585,27 +847,155 @@ impl LocalStreamManagerCore {
         Ok(rxs)
     }
 
-    fn build_actors(&mut self, actors: &[ActorId], env: StreamEnvironment) -> Result<()> {
+    fn build_actors(
+        &mut self,
+        actors: &[ActorId],
+        env: StreamEnvironment,
+        core_ref: Arc<Mutex<LocalStreamManagerCore>>,
+    ) -> Result<()> {
         for actor_id in actors {
             let actor_id = *actor_id;
-            let actor = self.actors.remove(&actor_id).unwrap();
-            let executor =
-                self.create_nodes(actor.fragment_id, actor_id, actor.get_nodes()?, env.clone())?;
-
-            let dispatcher = self.create_dispatcher(executor, &actor.dispatcher, actor_id)?;
-            let actor = Actor::new(dispatcher, actor_id, self.context.clone());
-            self.handles.insert(
-                actor_id,
-                madsim::task::spawn(async move {
-                    // unwrap the actor result to panic on error
-                    actor.run().await.expect("actor failed");
-                }),
-            );
+            // Keep the actor definition around (rather than removing it) so a restart can
+            // rebuild the same executor tree and dispatcher from scratch.
+            let actor = self.actors.get(&actor_id).unwrap().clone();
+            self.supervisors.entry(actor_id).or_insert_with(|| {
+                Supervisor::new(self.config.restart_policy(), default_backoff())
+            });
+            self.actor_statuses
+                .lock()
+                .insert(actor_id, ActorStatus::Building);
+
+            let executor = self.build_actor_executor(&actor, actor_id, env.clone())?;
+            self.spawn_supervised_actor(actor_id, actor, executor, env.clone(), core_ref.clone())?;
         }
 
         Ok(())
     }
 
+    fn build_actor_executor(
+        &mut self,
+        actor: &stream_plan::StreamActor,
+        actor_id: ActorId,
+        env: StreamEnvironment,
+    ) -> Result<BoxedExecutor> {
+        self.create_nodes(actor.fragment_id, actor_id, actor.get_nodes()?, env)
+    }
+
+    /// Spawn one actor under supervision: when its `run()` future fails, consult the actor's
+    /// `Supervisor` to decide whether (and after how long) to rebuild and respawn it. Retries
+    /// exhausted (or policy `Never`) marks the actor terminally failed instead of panicking the
+    /// whole worker.
+    fn spawn_supervised_actor(
+        &mut self,
+        actor_id: ActorId,
+        actor_def: stream_plan::StreamActor,
+        executor: BoxedExecutor,
+        env: StreamEnvironment,
+        core_ref: Arc<Mutex<LocalStreamManagerCore>>,
+    ) -> Result<()> {
+        let dispatcher = self.create_dispatcher(executor, &actor_def.dispatcher, actor_id)?;
+        let ctx = self.context.clone();
+        let actor = Actor::new(dispatcher, actor_id, ctx.clone());
+        let backpressure = self.backpressure.clone();
+        let down_edge_ids: Vec<UpDownActorIds> = actor_def
+            .dispatcher
+            .iter()
+            .flat_map(|d| d.downstream_actor_id.iter())
+            .map(|down_id| (actor_id, *down_id))
+            .collect();
+        self.actor_statuses
+            .lock()
+            .insert(actor_id, ActorStatus::Running);
+        self.handles.insert(
+            actor_id,
+            madsim::task::spawn(async move {
+                let mut started_at = std::time::Instant::now();
+                let mut actor = actor;
+                loop {
+                    // A panic inside `run()` is turned into an error, so it goes through the same
+                    // restart policy as a returned error.
+                    let result = match futures::FutureExt::catch_unwind(
+                        std::panic::AssertUnwindSafe(actor.run()),
+                    )
+                    .await
+                    {
+                        Ok(result) => result,
+                        Err(_) => Err(RwError::from(ErrorCode::InternalError(format!(
+                            "actor {} panicked",
+                            actor_id
+                        )))),
+                    };
+                    let Err(e) = result else { return };
+
+                    let delay = {
+                        let mut core = core_ref.lock();
+                        if let Some(supervisor) = core.supervisors.get_mut(&actor_id) {
+                            supervisor.on_stable_run(started_at.elapsed());
+                            supervisor.on_failure(&e)
+                        } else {
+                            None
+                        }
+                    };
+                    let Some(delay) = delay else {
+                        error!("actor {} failed and will not be restarted: {}", actor_id, e);
+                        let mut core = core_ref.lock();
+                        core.actor_statuses
+                            .lock()
+                            .insert(actor_id, ActorStatus::Failed(e.to_string()));
+                        core.record_exit_status(actor_id, ActorExitStatus::Failed(e));
+                        return;
+                    };
+                    error!(
+                        "actor {} failed: {}, restarting in {:?}",
+                        actor_id, e, delay
+                    );
+                    core_ref
+                        .lock()
+                        .actor_statuses
+                        .lock()
+                        .insert(actor_id, ActorStatus::Restarting);
+                    tokio::time::sleep(delay).await;
+
+                    // Don't rebuild straight back into a downstream that's still backed up past
+                    // the high watermark -- respawning into persistent congestion just thrashes
+                    // the restart budget without the consumer having had a chance to catch up.
+                    for &down_id in &down_edge_ids {
+                        backpressure.wait_for_credit(down_id).await;
+                    }
+
+                    let rebuilt = {
+                        let mut core = core_ref.lock();
+                        core.build_actor_executor(&actor_def, actor_id, env.clone())
+                            .and_then(|executor| {
+                                core.create_dispatcher(executor, &actor_def.dispatcher, actor_id)
+                            })
+                    };
+                    match rebuilt {
+                        Ok(dispatcher) => {
+                            actor = Actor::new(dispatcher, actor_id, ctx.clone());
+                            started_at = std::time::Instant::now();
+                            core_ref
+                                .lock()
+                                .actor_statuses
+                                .lock()
+                                .insert(actor_id, ActorStatus::Running);
+                        }
+                        Err(e) => {
+                            error!("failed to rebuild actor {} for restart: {}", actor_id, e);
+                            let mut core = core_ref.lock();
+                            core.actor_statuses
+                                .lock()
+                                .insert(actor_id, ActorStatus::Failed(e.to_string()));
+                            core.record_exit_status(actor_id, ActorExitStatus::Failed(e));
+                            return;
+                        }
+                    }
+                }
+            }),
+        );
+        Ok(())
+    }
+
     pub fn take_all_handles(&mut self) -> Result<HashMap<ActorId, ActorHandle>> {
         Ok(std::mem::take(&mut self.handles))
     }
@@ -646,11 +1036,26 @@ impl LocalStreamManagerCore {
     fn drop_actor(&mut self, actor_id: ActorId) {
         let mut handle = self.handles.remove(&actor_id).unwrap();
         self.context.retain(|&(up_id, _)| up_id != actor_id);
+        self.backpressure
+            .retain(|&(up_id, down_id)| up_id != actor_id && down_id != actor_id);
 
         self.actor_infos.remove(&actor_id);
         self.actors.remove(&actor_id);
+        // Otherwise `actor_statuses()` keeps reporting a dropped actor as `Running`/`Failed(...)`
+        // forever, and `supervisors`/`applied_revisions`/`exit_statuses` grow unbounded across
+        // actor churn.
+        self.supervisors.remove(&actor_id);
+        self.applied_revisions.remove(&actor_id);
+        self.actor_statuses.lock().remove(&actor_id);
+        self.exit_statuses.lock().remove(&actor_id);
+        self.retired_actor_ids.insert(actor_id);
         // Task should have already stopped when this method is invoked.
         handle.abort();
+        if let Some(linked) = self.linked_tasks.remove(&actor_id) {
+            for mut handle in linked {
+                handle.abort();
+            }
+        }
     }
 
     /// `drop_all_actors` is invoked by meta node via RPC once the stop barrier arrives at all the
@@ -658,17 +1063,55 @@ impl LocalStreamManagerCore {
     fn drop_all_actors(&mut self) {
         for (actor_id, mut handle) in self.handles.drain() {
             self.context.retain(|&(up_id, _)| up_id != actor_id);
+            self.backpressure
+                .retain(|&(up_id, down_id)| up_id != actor_id && down_id != actor_id);
             self.actors.remove(&actor_id);
+            self.retired_actor_ids.insert(actor_id);
             // Task should have already stopped when this method is invoked.
             handle.abort();
+            if let Some(linked) = self.linked_tasks.remove(&actor_id) {
+                for mut handle in linked {
+                    handle.abort();
+                }
+            }
         }
         self.actor_infos.clear();
+        // Same reasoning as `drop_actor`: these would otherwise keep reporting/growing for every
+        // actor this node ever ran.
+        self.supervisors.clear();
+        self.applied_revisions.clear();
+        self.actor_statuses.lock().clear();
+        self.exit_statuses.lock().clear();
+    }
+
+    /// Resolves the chain node's upstream the same way `build_channel_for_chain_node` used to,
+    /// but classifies a miss instead of flattening it into one `InternalError`. This only covers
+    /// upstream ids that aren't in `actor_infos` at all, so we have no host to consult
+    /// `peer_states` against; a known-disconnected host for an upstream we *do* have info for is
+    /// instead handled directly in `build_channel_for_chain_node`. `actor_infos` being additive
+    /// (see `update_actor_info`) means absence from it alone can't distinguish "permanently
+    /// retired" from "not broadcast yet" -- but `retired_actor_ids` can, since this node itself
+    /// tore the actor down, so that's checked first.
+    fn classify_missing_upstream(&self, upstream_actor_id: ActorId) -> UpstreamUnavailable {
+        let availability = if self.retired_actor_ids.contains(&upstream_actor_id) {
+            UpstreamAvailability::Unknown
+        } else {
+            UpstreamAvailability::NotYetRegistered
+        };
+        UpstreamUnavailable {
+            upstream_actor_id,
+            availability,
+        }
     }
 
+    /// Pushes onto `pending` the upstream actor ids for this actor's chain nodes that could not be
+    /// wired up yet; the permanent `Unknown` case is returned as an error instead. Callers should
+    /// record the pending ones in `pending_chain_edges` and retry on the next `update_actors`.
     fn build_channel_for_chain_node(
         &self,
         actor_id: ActorId,
         stream_node: &stream_plan::StreamNode,
+        pending: &mut Vec<ActorId>,
     ) -> Result<()> {
         if let NodeBody::Chain(_) = stream_node.node_body.as_ref().unwrap() {
             // Create channel based on upstream actor id for [`ChainNode`], check if upstream
@@ -686,11 +1129,30 @@ impl LocalStreamManagerCore {
             )?;
             for upstream_actor_id in &merge.upstream_actor_id {
                 if !self.actor_infos.contains_key(upstream_actor_id) {
-                    return Err(ErrorCode::InternalError(format!(
-                        "chain upstream actor {} not exists",
-                        upstream_actor_id
-                    ))
-                    .into());
+                    let unavailable = self.classify_missing_upstream(*upstream_actor_id);
+                    if !unavailable.is_retriable() {
+                        return Err(
+                            ErrorCode::InternalError(unavailable.to_string()).into()
+                        );
+                    }
+                    pending.push(*upstream_actor_id);
+                    continue;
+                }
+                let upstream_addr: HostAddr = self.get_actor_info(upstream_actor_id)?.get_host()?.into();
+                if !is_local_address(&upstream_addr, &self.context.addr)
+                    && !self.peer_states.is_connected(&upstream_addr)
+                {
+                    // The upstream is registered but its host is known-dead; go through the same
+                    // `UpstreamAvailability` classification as `classify_missing_upstream` rather
+                    // than a bare `pending.push`, so `Disconnected` -- always retriable -- is an
+                    // explicit, documented case instead of implicit fallthrough behavior.
+                    let unavailable = UpstreamUnavailable {
+                        upstream_actor_id: *upstream_actor_id,
+                        availability: UpstreamAvailability::Disconnected,
+                    };
+                    debug_assert!(unavailable.is_retriable());
+                    pending.push(*upstream_actor_id);
+                    continue;
                 }
                 let (tx, rx) = channel(LOCAL_OUTPUT_CHANNEL_SIZE);
                 let up_down_ids = (*upstream_actor_id, actor_id);
@@ -699,16 +1161,20 @@ impl LocalStreamManagerCore {
             }
         }
         for child in &stream_node.input {
-            self.build_channel_for_chain_node(actor_id, child)?;
+            self.build_channel_for_chain_node(actor_id, child, pending)?;
         }
         Ok(())
     }
 
+    /// Applies a delta of added actors and hanging channels. Returns `true` if some chain-node
+    /// upstream couldn't be wired up yet and was recorded in `pending_chain_edges` for retry
+    /// (i.e. the caller should re-drive the build once the peer reconnects or the remaining
+    /// actors arrive), `false` if the graph is fully wired.
     fn update_actors(
         &mut self,
         actors: &[stream_plan::StreamActor],
         hanging_channels: &[stream_service::HangingChannel],
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let local_actor_ids: HashSet<ActorId> = HashSet::from_iter(
             actors
                 .iter()
@@ -717,19 +1183,59 @@ impl LocalStreamManagerCore {
                 .into_iter(),
         );
 
+        // This is an incremental apply: an actor whose revision matches the one we already
+        // applied (e.g. the meta service re-pushing an unchanged plan, or a previous call that
+        // covered part of this delta) is left untouched rather than hard-erroring, so a single
+        // reconfiguration no longer forces the whole graph to be torn down and rebuilt. An actor
+        // id we've seen before but at a different revision is treated like a new one, since its
+        // dispatcher/nodes may have changed. Only genuinely new-or-changed actors get their
+        // channels (re)allocated below.
+        let mut newly_added = Vec::with_capacity(actors.len());
         for actor in actors {
-            let ret = self.actors.insert(actor.get_actor_id(), actor.clone());
-            if ret.is_some() {
-                return Err(ErrorCode::InternalError(format!(
-                    "duplicated actor {}",
-                    actor.get_actor_id()
-                ))
-                .into());
+            let actor_id = actor.get_actor_id();
+            if self.applied_revisions.get(&actor_id) == Some(&actor.revision) {
+                continue;
             }
+            self.actors.insert(actor_id, actor.clone());
+            self.applied_revisions.insert(actor_id, actor.revision);
+            newly_added.push(actor_id);
         }
 
-        for (current_id, actor) in &self.actors {
-            self.build_channel_for_chain_node(*current_id, actor.nodes.as_ref().unwrap())?;
+        // Retry edges left pending by an earlier call before attempting the newly-added actors,
+        // since the actors they were waiting on may have just arrived in this batch.
+        let mut still_pending = HashMap::new();
+        for (current_id, awaited) in self.pending_chain_edges.drain() {
+            let mut pending = Vec::new();
+            for upstream_actor_id in awaited {
+                let resolvable = match self.actor_infos.get(&upstream_actor_id) {
+                    Some(info) => {
+                        let upstream_addr: HostAddr = info.get_host()?.into();
+                        is_local_address(&upstream_addr, &self.context.addr)
+                            || self.peer_states.is_connected(&upstream_addr)
+                    }
+                    None => false,
+                };
+                if resolvable {
+                    let (tx, rx) = channel(LOCAL_OUTPUT_CHANNEL_SIZE);
+                    self.context
+                        .add_channel_pairs((upstream_actor_id, current_id), (Some(tx), Some(rx)));
+                } else {
+                    pending.push(upstream_actor_id);
+                }
+            }
+            if !pending.is_empty() {
+                still_pending.insert(current_id, pending);
+            }
+        }
+        self.pending_chain_edges = still_pending;
+
+        for current_id in &newly_added {
+            let actor = self.actors.get(current_id).unwrap().clone();
+            let mut pending = Vec::new();
+            self.build_channel_for_chain_node(*current_id, actor.nodes.as_ref().unwrap(), &mut pending)?;
+            if !pending.is_empty() {
+                self.pending_chain_edges.insert(*current_id, pending);
+            }
 
             // At this time, the graph might not be complete, so we do not check if downstream
             // has `current_id` as upstream.
@@ -739,7 +1245,7 @@ impl LocalStreamManagerCore {
                 .flat_map(|x| x.downstream_actor_id.iter())
                 .map(|id| (*current_id, *id))
                 .collect_vec();
-            update_upstreams(&self.context, &down_id);
+            update_upstreams(&self.context, &self.backpressure, &down_id);
 
             // Add remote input channels.
             let mut up_id = vec![];
@@ -748,9 +1254,14 @@ impl LocalStreamManagerCore {
                     up_id.push((*upstream_id, *current_id));
                 }
             }
-            update_upstreams(&self.context, &up_id);
+            update_upstreams(&self.context, &self.backpressure, &up_id);
         }
 
+        // Mirroring Sentry Relay's defensive handling of inconsistent config deltas: a malformed
+        // hanging channel (wrong number of remote sides, or referencing a local actor outside
+        // this batch) is logged and discarded rather than aborting the whole update, since the
+        // rest of the delta is still valid and a torn-down update would be worse than a dropped
+        // edge that meta can simply re-send.
         for hanging_channel in hanging_channels {
             match (&hanging_channel.upstream, &hanging_channel.downstream) {
                 (
@@ -760,6 +1271,13 @@ impl LocalStreamManagerCore {
                         host: None,
                     }),
                 ) => {
+                    if !local_actor_ids.contains(down_id) {
+                        tracing::warn!(
+                            "discarding hanging channel: downstream actor {} is not part of this update batch",
+                            down_id
+                        );
+                        continue;
+                    }
                     let up_down_ids = (up.actor_id, *down_id);
                     let (tx, rx) = channel(LOCAL_OUTPUT_CHANNEL_SIZE);
                     self.context
@@ -772,20 +1290,116 @@ impl LocalStreamManagerCore {
                     }),
                     Some(down),
                 ) => {
+                    if !local_actor_ids.contains(up_id) {
+                        tracing::warn!(
+                            "discarding hanging channel: upstream actor {} is not part of this update batch",
+                            up_id
+                        );
+                        continue;
+                    }
                     let up_down_ids = (*up_id, down.actor_id);
                     let (tx, rx) = channel(LOCAL_OUTPUT_CHANNEL_SIZE);
                     self.context
                         .add_channel_pairs(up_down_ids, (Some(tx), Some(rx)));
                 }
                 _ => {
-                    return Err(ErrorCode::InternalError(format!(
-                        "hanging channel should has exactly one remote side: {:?}",
+                    tracing::warn!(
+                        "discarding malformed hanging channel, expected exactly one remote side: {:?}",
                         hanging_channel,
-                    ))
-                    .into())
+                    );
                 }
             }
         }
-        Ok(())
+
+        if self.pending_chain_edges.is_empty() {
+            self.finalize_dense_layout();
+        } else {
+            // The graph is still mid-flight; the resolved-downstream lists would be incomplete,
+            // so fall back to the `HashMap`-keyed path until a later call closes the gap.
+            self.dense_actors = None;
+        }
+
+        Ok(!self.pending_chain_edges.is_empty())
+    }
+
+    /// Compacts the actor graph into a [`DenseActorTable`] once it's known-complete (no pending
+    /// chain edges), so dispatcher construction (see `resolve_downstream_ids`) can index into
+    /// contiguous `Vec`s; the per-message send loop still goes through `SharedContext`'s channel
+    /// map. Cheap enough to redo on every batch that closes out pending
+    /// edges, since it only runs on this cold setup path, not per-message.
+    fn finalize_dense_layout(&mut self) {
+        let actor_ids: Vec<ActorId> = self.actors.keys().copied().collect();
+        let resolved_edges: Vec<(ActorId, ActorId)> = self
+            .actors
+            .values()
+            .flat_map(|actor| {
+                let actor_id = actor.get_actor_id();
+                actor
+                    .dispatcher
+                    .iter()
+                    .flat_map(|d| d.downstream_actor_id.iter())
+                    .map(move |down_id| (actor_id, *down_id))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        self.dense_actors = Some(DenseActorTable::build(&actor_ids, &resolved_edges));
+    }
+
+    /// Assembles a [`TopologySnapshot`] of the wiring this core currently knows about: every
+    /// built actor, the downstream edges from each actor's dispatcher(s), the upstream edges
+    /// considered resolved, and the chain edges still recorded in `pending_chain_edges`.
+    fn topology_snapshot(&self) -> TopologySnapshot {
+        let actor_ids: Vec<ActorId> = self.actors.keys().copied().collect();
+
+        let downstream_edges: Vec<(ActorId, ActorId)> = self
+            .actors
+            .values()
+            .flat_map(|actor| {
+                let actor_id = actor.get_actor_id();
+                actor
+                    .dispatcher
+                    .iter()
+                    .flat_map(|d| d.downstream_actor_id.iter())
+                    .map(move |down_id| (actor_id, *down_id))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let pending_edges: Vec<(ActorId, ActorId)> = self
+            .pending_chain_edges
+            .iter()
+            .flat_map(|(down_id, ups)| ups.iter().map(move |up_id| (*down_id, *up_id)))
+            .collect();
+        let pending_set: HashSet<(ActorId, ActorId)> = pending_edges.iter().copied().collect();
+
+        let resolved_channels: Vec<(ActorId, ActorId)> = self
+            .actors
+            .values()
+            .flat_map(|actor| {
+                let actor_id = actor.get_actor_id();
+                actor
+                    .get_upstream_actor_id()
+                    .iter()
+                    .map(move |up_id| (*up_id, actor_id))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|(up_id, down_id)| !pending_set.contains(&(*down_id, *up_id)))
+            .chain(downstream_edges.iter().copied())
+            .collect();
+
+        TopologySnapshot {
+            actor_ids,
+            downstream_edges,
+            resolved_channels,
+            pending_edges,
+        }
+    }
+
+    /// Re-inserts chain edges recorded in a loaded [`TopologySnapshot`] into
+    /// `pending_chain_edges`, so a restarting node remembers what it was still waiting on.
+    fn restore_pending_chain_edges(&mut self, pending_edges: Vec<(ActorId, ActorId)>) {
+        for (down_id, up_id) in pending_edges {
+            self.pending_chain_edges.entry(down_id).or_default().push(up_id);
+        }
     }
 }
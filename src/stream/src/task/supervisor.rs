@@ -0,0 +1,166 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Actor restart policies and the exponential backoff used between restart attempts.
+
+use std::time::Duration;
+
+use risingwave_common::error::RwError;
+
+/// Where one actor currently is in its lifecycle: set by `build_actors` and by supervised
+/// restarts. Barrier collection does not update it, so nothing in `LocalStreamManagerCore` sets
+/// the `Barrier` variant. Queried via
+/// `LocalStreamManager::actor_statuses` so meta/operators can poll live actor health across the
+/// cluster without scraping logs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActorStatus {
+    /// The executor tree and dispatcher are being assembled.
+    Building,
+    /// Running normally, not currently inside a barrier.
+    Running,
+    /// Currently processing the barrier for `epoch`.
+    Barrier { epoch: u64 },
+    /// Failed and is being rebuilt after a supervised restart.
+    Restarting,
+    /// Terminally failed; see `ActorExitStatus` for the reason.
+    Failed(String),
+}
+
+/// The terminal outcome of an actor that is no longer going to be restarted, recorded into
+/// `LocalStreamManagerCore`'s exit-status table via an actor's `exit_hook` instead of unwinding
+/// the process. This lets the barrier manager observe which actor failed and at what epoch, and
+/// fail the in-flight barrier cleanly so meta can trigger recovery.
+#[derive(Debug, Clone)]
+pub enum ActorExitStatus {
+    /// The actor finished normally (e.g. in response to a `Stop` mutation).
+    Finished,
+    /// The actor's `run()` future returned an error and the restart policy gave up.
+    Failed(RwError),
+}
+
+/// How a fragment's actors should be recovered when their `run()` future returns an error or
+/// panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Never restart; a failed actor stays failed.
+    Never,
+    /// Restart up to `max_retries` times, then give up and mark the actor terminally failed.
+    OnFailure { max_retries: u32 },
+    /// Always restart, with no retry limit.
+    Always,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::Never
+    }
+}
+
+impl RestartPolicy {
+    /// Whether another restart attempt is allowed given how many restarts have happened so far.
+    fn allows(&self, restart_count: u32) -> bool {
+        match self {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure { max_retries } => restart_count < *max_retries,
+            RestartPolicy::Always => true,
+        }
+    }
+}
+
+/// Exponential backoff between restart attempts, with a stability window after which a
+/// successfully-running actor resets back to `min`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    current: Duration,
+    min: Duration,
+    max: Duration,
+    multiplier: f64,
+    /// How long an actor must run without failing before `current` resets to `min`.
+    stability_window: Duration,
+}
+
+impl Backoff {
+    pub fn new(min: Duration, max: Duration, multiplier: f64, stability_window: Duration) -> Self {
+        Self {
+            current: min,
+            min,
+            max,
+            multiplier,
+            stability_window,
+        }
+    }
+
+    /// The delay to sleep before the next restart attempt. Also advances `current` towards `max`.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        let scaled = self.current.mul_f64(self.multiplier);
+        self.current = scaled.min(self.max);
+        delay
+    }
+
+    /// Called once an actor has been running again for at least `stability_window` without
+    /// failing, so a brief blip doesn't leave future restarts waiting at the `max` backoff.
+    pub fn note_stable_run(&mut self, uptime: Duration) {
+        if uptime >= self.stability_window {
+            self.current = self.min;
+        }
+    }
+}
+
+/// Per-actor restart bookkeeping kept by `LocalStreamManagerCore` so restart counts and the last
+/// error are queryable without reaching into the running actor.
+#[derive(Debug, Clone, Default)]
+pub struct RestartState {
+    pub restart_count: u32,
+    pub last_error: Option<String>,
+}
+
+/// Decides whether a failed actor should be rebuilt, tracking the backoff and restart count for
+/// one actor across its lifetime.
+#[derive(Debug, Clone)]
+pub struct Supervisor {
+    policy: RestartPolicy,
+    backoff: Backoff,
+    state: RestartState,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy, backoff: Backoff) -> Self {
+        Self {
+            policy,
+            backoff,
+            state: RestartState::default(),
+        }
+    }
+
+    /// Records a failure and returns the delay to wait before rebuilding the actor, or `None` if
+    /// the policy says to give up (in which case the actor should be marked terminally failed).
+    pub fn on_failure(&mut self, error: impl ToString) -> Option<Duration> {
+        if !self.policy.allows(self.state.restart_count) {
+            self.state.last_error = Some(error.to_string());
+            return None;
+        }
+        self.state.restart_count += 1;
+        self.state.last_error = Some(error.to_string());
+        Some(self.backoff.next_delay())
+    }
+
+    pub fn on_stable_run(&mut self, uptime: Duration) {
+        self.backoff.note_stable_run(uptime);
+    }
+
+    pub fn state(&self) -> &RestartState {
+        &self.state
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A stable binary snapshot of the actor/channel topology `LocalStreamManagerCore` assembles in
+//! `update_actors`, so the exact wiring of a compute node can be dumped for post-mortem debugging
+//! or reloaded to rehydrate the bookkeeping across a restart without waiting on meta to
+//! re-broadcast the full plan. Mirrors the `Writeable`/`Readable` hop-by-hop pattern used to
+//! serialize a `Route`, rather than reaching for a general-purpose serde format, since the layout
+//! needs to stay stable across binary versions for debugging dumps taken in the field.
+//!
+//! Note that only the bookkeeping round-trips: the actual channel senders/receivers are per-process
+//! `mpsc` endpoints and can't be serialized. Loading a snapshot back in (see
+//! `LocalStreamManager::load_topology`) restores only which chain edges are still pending; the
+//! resolved edges and the channels themselves are not restored.
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use risingwave_common::error::{ErrorCode, Result, RwError};
+
+use crate::task::ActorId;
+
+/// Writes `Self` to `buf` in the topology snapshot's binary format.
+pub trait Writeable {
+    fn write_to(&self, buf: &mut BytesMut);
+}
+
+/// Reads `Self` back out of `buf`, consuming exactly the bytes `write_to` produced.
+pub trait Readable: Sized {
+    fn read_from(buf: &mut impl Buf) -> Result<Self>;
+}
+
+fn eof() -> RwError {
+    ErrorCode::InternalError("truncated topology snapshot".to_string()).into()
+}
+
+impl Writeable for ActorId {
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.put_u32_le(*self);
+    }
+}
+
+impl Readable for ActorId {
+    fn read_from(buf: &mut impl Buf) -> Result<Self> {
+        if buf.remaining() < 4 {
+            return Err(eof());
+        }
+        Ok(buf.get_u32_le())
+    }
+}
+
+impl<T: Writeable> Writeable for Vec<T> {
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.put_u32_le(self.len() as u32);
+        for item in self {
+            item.write_to(buf);
+        }
+    }
+}
+
+impl<T: Readable> Readable for Vec<T> {
+    fn read_from(buf: &mut impl Buf) -> Result<Self> {
+        if buf.remaining() < 4 {
+            return Err(eof());
+        }
+        let len = buf.get_u32_le() as usize;
+        (0..len).map(|_| T::read_from(buf)).collect()
+    }
+}
+
+impl<A: Writeable, B: Writeable> Writeable for (A, B) {
+    fn write_to(&self, buf: &mut BytesMut) {
+        self.0.write_to(buf);
+        self.1.write_to(buf);
+    }
+}
+
+impl<A: Readable, B: Readable> Readable for (A, B) {
+    fn read_from(buf: &mut impl Buf) -> Result<Self> {
+        Ok((A::read_from(buf)?, B::read_from(buf)?))
+    }
+}
+
+/// Magic + format version prefixed to every snapshot so a debugging dump from an incompatible
+/// binary is rejected instead of silently misparsed.
+const MAGIC: u32 = 0x5253_5453; // "RSTS" (RisingWave Stream ToplogySnapshot)
+const VERSION: u32 = 1;
+
+/// A point-in-time dump of one compute node's actor/channel wiring.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopologySnapshot {
+    /// Every actor id currently built on this node.
+    pub actor_ids: Vec<ActorId>,
+    /// `(actor_id, downstream_actor_id)` edges from each actor's dispatcher(s).
+    pub downstream_edges: Vec<(ActorId, ActorId)>,
+    /// `(upstream_actor_id, downstream_actor_id)` edges considered resolved: each built actor's
+    /// upstream edges that aren't in `pending_edges`, followed by every `downstream_edges` entry.
+    /// Either side may be an actor hosted on another node.
+    pub resolved_channels: Vec<(ActorId, ActorId)>,
+    /// `(downstream_actor_id, upstream_actor_id)` chain-node edges still awaiting their upstream,
+    /// mirroring `LocalStreamManagerCore::pending_chain_edges`.
+    pub pending_edges: Vec<(ActorId, ActorId)>,
+}
+
+impl TopologySnapshot {
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        buf.put_u32_le(MAGIC);
+        buf.put_u32_le(VERSION);
+        self.actor_ids.write_to(&mut buf);
+        self.downstream_edges.write_to(&mut buf);
+        self.resolved_channels.write_to(&mut buf);
+        self.pending_edges.write_to(&mut buf);
+        buf.freeze()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut buf = bytes;
+        if buf.remaining() < 8 {
+            return Err(eof());
+        }
+        let magic = buf.get_u32_le();
+        if magic != MAGIC {
+            return Err(ErrorCode::InternalError(format!(
+                "not a topology snapshot: bad magic {:#x}",
+                magic
+            ))
+            .into());
+        }
+        let version = buf.get_u32_le();
+        if version != VERSION {
+            return Err(ErrorCode::InternalError(format!(
+                "unsupported topology snapshot version {}, expected {}",
+                version, VERSION
+            ))
+            .into());
+        }
+        Ok(Self {
+            actor_ids: Readable::read_from(&mut buf)?,
+            downstream_edges: Readable::read_from(&mut buf)?,
+            resolved_channels: Readable::read_from(&mut buf)?,
+            pending_edges: Readable::read_from(&mut buf)?,
+        })
+    }
+}
@@ -0,0 +1,58 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Distinguishes why a chain node's upstream actor can't be found yet, instead of flattening
+//! every case into one `InternalError`. Building the actor graph is incremental (see
+//! `LocalStreamManagerCore::update_actors`), so "not found" during `build_channel_for_chain_node`
+//! doesn't always mean the upstream is gone for good.
+
+use crate::task::ActorId;
+
+/// Why an upstream actor referenced by a `ChainNode` isn't resolvable right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpstreamAvailability {
+    /// The upstream id is legitimately unknown or has been retired; this is a permanent error
+    /// and the caller should not retry.
+    Unknown,
+    /// The upstream's host node is known but currently disconnected.
+    Disconnected,
+    /// The upstream actor hasn't been registered yet because the graph build is mid-flight; the
+    /// edge should be recorded as pending and resolved once the remaining actors arrive.
+    NotYetRegistered,
+}
+
+/// Raised in place of the old flat `InternalError("chain upstream actor {} not exists")`.
+#[derive(Debug, Clone)]
+pub struct UpstreamUnavailable {
+    pub upstream_actor_id: ActorId,
+    pub availability: UpstreamAvailability,
+}
+
+impl UpstreamUnavailable {
+    /// Whether `update_actors` should report this as retriable (the edge is recorded as pending
+    /// and lazily created once the upstream resolves) rather than fail the whole build outright.
+    pub fn is_retriable(&self) -> bool {
+        !matches!(self.availability, UpstreamAvailability::Unknown)
+    }
+}
+
+impl std::fmt::Display for UpstreamUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "chain upstream actor {} unavailable: {:?}",
+            self.upstream_actor_id, self.availability
+        )
+    }
+}